@@ -0,0 +1,14 @@
+/// Returns the number of bytes a bitmap of `rows` x `cols` cells needs, one
+/// bit per cell row by row from the north-west.
+pub fn bitmap_len(rows: usize, cols: usize) -> usize {
+    rows.saturating_mul(cols).div_ceil(8)
+}
+
+/// Returns bit `index` of a bitmap.
+pub fn get_bit(bitmap: &[u8], index: usize) -> bool {
+    bitmap[index / 8] & (1 << (index % 8)) != 0
+}
+
+pub(crate) fn set_bit(bitmap: &mut [u8], index: usize) {
+    bitmap[index / 8] |= 1 << (index % 8);
+}
@@ -0,0 +1,208 @@
+use core::cell::Cell;
+
+use heapless::{String, Vec};
+
+use crate::{Error, HgtReader};
+
+/// One cached block of an HGT file.
+#[derive(Debug, Clone)]
+struct CacheLine<const LINE_BYTES: usize> {
+    /// Position of the first byte in the file.
+    start: u64,
+    /// Number of valid bytes, shorter than `LINE_BYTES` at the end of the file.
+    len: usize,
+    /// Time of the last access, used for LRU eviction.
+    used: u32,
+    data: [u8; LINE_BYTES],
+}
+
+/// CachedReader wraps an [`HgtReader`] and keeps the most recently read
+/// blocks of the open file in memory.
+///
+/// The cache holds `LINES` blocks of `LINE_BYTES` bytes each; both are fixed
+/// at compile time, no allocator is needed. Blocks are aligned to multiples
+/// of `LINE_BYTES` from the start of the file, so with
+/// `LINE_BYTES = 2 * resolution.point_per_degree()` every block is exactly one
+/// row of the tile. When all lines are in use, the least recently used one
+/// is replaced.
+///
+/// The cache is dropped whenever a different file is opened. The length of
+/// the last block is taken from the expected length passed to
+/// [`HgtReader::check_hgt_file`], which [`crate::Tile`] always calls.
+///
+/// # Example
+/// ```ignore
+/// // keep 3 rows of an SRTM3 tile
+/// let reader = CachedReader::<_, 3, 2402>::new(MyReader::new());
+/// let mut tile = Tile::<CachedReader<MyReader, 3, 2402>>::new(Resolution::SRTM3, reader);
+/// ```
+#[derive(Debug)]
+pub struct CachedReader<R: HgtReader, const LINES: usize, const LINE_BYTES: usize> {
+    reader: R,
+    file_name: String<12>,
+    /// Expected length of the open file, 0 if unknown.
+    file_len: Cell<u64>,
+    lines: Vec<CacheLine<LINE_BYTES>, LINES>,
+    clock: u32,
+    hits: u32,
+    misses: u32,
+}
+
+impl<R: HgtReader, const LINES: usize, const LINE_BYTES: usize> CachedReader<R, LINES, LINE_BYTES> {
+    /// Creates an empty cache in front of the given reader.
+    ///
+    /// `LINES` must not be zero and `LINE_BYTES` must be a non-zero multiple
+    /// of 2, so that a sample never spans two lines.
+    pub fn new(reader: R) -> Self {
+        const { assert!(LINES > 0 && LINE_BYTES > 0 && LINE_BYTES.is_multiple_of(2)) };
+        CachedReader {
+            reader,
+            file_name: String::new(),
+            file_len: Cell::new(0),
+            lines: Vec::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the number of reads served from the cache.
+    pub fn hits(&self) -> u32 {
+        self.hits
+    }
+
+    /// Returns the number of reads that had to go to the wrapped reader.
+    pub fn misses(&self) -> u32 {
+        self.misses
+    }
+
+    /// Resets the hit and miss counters.
+    pub fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    /// Drops all cached blocks.
+    pub fn invalidate(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Returns a reference to the wrapped reader.
+    pub fn inner(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consumes the cache and returns the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Returns the index of the line holding `pos`, loading it on a miss.
+    fn line_for(&mut self, pos: u64) -> Result<usize, Error> {
+        self.clock = self.clock.wrapping_add(1);
+        let start = pos - pos % LINE_BYTES as u64;
+        if let Some(index) = self.lines.iter().position(|line| line.start == start) {
+            self.hits = self.hits.wrapping_add(1);
+            self.lines[index].used = self.clock;
+            return Ok(index);
+        }
+        self.misses = self.misses.wrapping_add(1);
+
+        let file_len = self.file_len.get();
+        let len = if file_len > start {
+            ((file_len - start) as usize).min(LINE_BYTES)
+        } else {
+            LINE_BYTES
+        };
+        let free = CacheLine {
+            start,
+            len: 0,
+            used: 0,
+            data: [0; LINE_BYTES],
+        };
+        let index = match self.lines.push(free) {
+            Ok(()) => self.lines.len() - 1,
+            // evict the least recently used line
+            Err(_) => self
+                .lines
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, line)| line.used)
+                .map(|(index, _)| index)
+                .ok_or(Error::BufferTooSmall)?,
+        };
+
+        let line = &mut self.lines[index];
+        // mark the line invalid until the read succeeded
+        line.start = u64::MAX;
+        self.reader.read_hgt_block(start, &mut line.data[..len])?;
+        line.start = start;
+        line.len = len;
+        line.used = self.clock;
+        Ok(index)
+    }
+}
+
+impl<R: HgtReader, const LINES: usize, const LINE_BYTES: usize> HgtReader
+    for CachedReader<R, LINES, LINE_BYTES>
+{
+    /// Opens the file with the wrapped reader,
+    /// the cache is dropped if the name differs from the open file.
+    fn open_hgt_file(&mut self, file_name: &str) -> Result<(), Error> {
+        if self.file_name != file_name {
+            self.invalidate();
+            self.file_name.clear();
+            self.file_len.set(0);
+        }
+        self.reader.open_hgt_file(file_name)?;
+        if self.file_name.is_empty() {
+            // names that do not fit are not tracked, the cache is then
+            // dropped on every open
+            self.file_name.push_str(file_name).ok();
+        }
+        Ok(())
+    }
+
+    /// Checks the size with the wrapped reader and remembers it on success.
+    fn check_hgt_file(&self, expt_len: u64) -> Result<(), Error> {
+        self.reader.check_hgt_file(expt_len)?;
+        self.file_len.set(expt_len);
+        Ok(())
+    }
+
+    fn read_hgt_data(&mut self, pos: u64, buffer: &mut [u8; 2]) -> Result<(), Error> {
+        let index = self.line_for(pos)?;
+        let line = &self.lines[index];
+        let offset = (pos - line.start) as usize;
+        if offset + 2 > line.len {
+            return Err(Error::IndexOutOfBounds);
+        }
+        buffer.copy_from_slice(&line.data[offset..offset + 2]);
+        Ok(())
+    }
+
+    fn read_hgt_block(&mut self, pos: u64, buffer: &mut [u8]) -> Result<(), Error> {
+        let mut done = 0;
+        while done < buffer.len() {
+            let pos = pos + done as u64;
+            let index = self.line_for(pos)?;
+            let line = &self.lines[index];
+            let offset = (pos - line.start) as usize;
+            if offset >= line.len {
+                return Err(Error::IndexOutOfBounds);
+            }
+            let len = (line.len - offset).min(buffer.len() - done);
+            buffer[done..done + len].copy_from_slice(&line.data[offset..offset + len]);
+            done += len;
+        }
+        Ok(())
+    }
+
+    /// Closes the file with the wrapped reader and drops the cache.
+    fn close_hgt_file(&mut self) -> Result<(), Error> {
+        self.invalidate();
+        self.file_name.clear();
+        self.file_len.set(0);
+        self.reader.close_hgt_file()
+    }
+}
@@ -0,0 +1,264 @@
+use super::Coord;
+use crate::{ElevationSource, Error, coords::floor_f64, resolutions::Resolution};
+
+/// The area swept by a vehicle in the next seconds of flight.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Corridor {
+    /// Current position.
+    pub position: Coord,
+    /// Ground track in degrees clockwise from north.
+    pub track: f64,
+    /// Ground speed in meters per second.
+    pub speed: f64,
+    /// Look-ahead time in seconds.
+    pub lookahead: f64,
+    /// Lateral buffer on each side of the track in meters.
+    pub half_width: f64,
+}
+
+impl Corridor {
+    /// Returns the length of the corridor in meters.
+    pub fn length(&self) -> f64 {
+        self.speed * self.lookahead
+    }
+}
+
+/// The highest terrain sample found in a corridor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CorridorMax {
+    /// Terrain height in meters.
+    pub height: i16,
+    pub coord: Coord,
+    /// Distance ahead along the track in meters.
+    pub along: f64,
+    /// Lateral offset in meters, positive to the right of the track.
+    pub across: f64,
+    /// Number of samples the scan looked at.
+    pub samples: usize,
+}
+
+impl CorridorMax {
+    /// Returns the vertical clearance in meters of an altitude above mean sea
+    /// level over the highest terrain, negative if the terrain is higher.
+    pub fn clearance(&self, altitude: f64) -> f64 {
+        altitude - self.height as f64
+    }
+}
+
+/// Returns the number of heights [`corridor_max`] reads for the corridor
+/// with data of the given resolution.
+pub fn sample_count(corridor: &Corridor, resolution: Resolution) -> usize {
+    Scan::new(corridor, resolution).count(usize::MAX)
+}
+
+/// Finds the highest terrain inside the corridor ahead of a vehicle.
+///
+/// The rectangle starting at the current position, `length()` meters long
+/// and `2 * half_width` meters wide, is scanned row by row; every cell of the
+/// source overlapping it is read, whatever the track, so even a single high
+/// cell is found. No memory besides the stack frame is used. Void samples are
+/// skipped. `max_samples` bounds the run time: a corridor with more cells is
+/// rejected rather than scanned partially; [`sample_count`] tells the number
+/// beforehand.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - a corridor dimension is negative or not finite, or the corridor covers more than `max_samples` cells.
+/// * `Error::InvalidData` - all samples are void.
+/// * any error of the source except `Error::InvalidData`.
+pub fn corridor_max<S: ElevationSource>(
+    source: &mut S,
+    corridor: &Corridor,
+    max_samples: usize,
+) -> Result<CorridorMax, Error> {
+    let length = corridor.length();
+    if !length.is_finite()
+        || length < 0.0
+        || !corridor.half_width.is_finite()
+        || corridor.half_width < 0.0
+        || !corridor.track.is_finite()
+    {
+        return Err(Error::InvalidParameter);
+    }
+    let scan = Scan::new(corridor, source.resolution());
+    if scan.count(max_samples) > max_samples {
+        return Err(Error::InvalidParameter);
+    }
+
+    let mut highest: Option<CorridorMax> = None;
+    let mut samples = 0;
+    for gy in scan.rows.0..=scan.rows.1 {
+        let Some((first, last)) = scan.columns(gy) else {
+            continue;
+        };
+        for gx in first..=last {
+            samples += 1;
+            let coord = scan.coord(gy, gx);
+            let height = match source.height_at(coord) {
+                Ok(height) => height,
+                Err(Error::InvalidData) => continue,
+                Err(err) => return Err(err),
+            };
+            if highest.is_none_or(|highest| height > highest.height) {
+                let (along, across) = scan.offsets(gy, gx);
+                highest = Some(CorridorMax {
+                    height,
+                    coord,
+                    along,
+                    across,
+                    samples: 0,
+                });
+            }
+        }
+    }
+    let mut highest = highest.ok_or(Error::InvalidData)?;
+    highest.samples = samples;
+    Ok(highest)
+}
+
+/// Tolerance in samples that keeps cells merely touching the corridor out.
+const EPSILON: f64 = 1e-6;
+
+/// The cells of the source overlapping a corridor, in global sample indices
+/// counting north and east from latitude and longitude 0.
+struct Scan {
+    /// Sample intervals per degree.
+    intervals: f64,
+    /// Position of the corridor in samples as (north, east).
+    origin: (f64, f64),
+    /// Cell size in meters as (east-west, north-south).
+    cell: (f64, f64),
+    /// Sine and cosine of the track.
+    track: (f64, f64),
+    length: f64,
+    half_width: f64,
+    /// Extent of the corridor in meters east of the position as (min, max).
+    east: (f64, f64),
+    /// First and last row.
+    rows: (i64, i64),
+}
+
+impl Scan {
+    fn new(corridor: &Corridor, resolution: Resolution) -> Scan {
+        let intervals = (resolution.point_per_degree() - 1) as f64;
+        let cell = resolution.cell_size(corridor.position.lat);
+        let angle = corridor.track.to_radians();
+        let track = (libm::sin(angle), libm::cos(angle));
+        let (length, half_width) = (corridor.length(), corridor.half_width);
+        let corners = [
+            (0.0, -half_width),
+            (0.0, half_width),
+            (length, -half_width),
+            (length, half_width),
+        ];
+        let (mut east, mut north) = ((f64::MAX, f64::MIN), (f64::MAX, f64::MIN));
+        for (along, across) in corners {
+            let (e, n) = (
+                along * track.0 + across * track.1,
+                along * track.1 - across * track.0,
+            );
+            east = (east.0.min(e), east.1.max(e));
+            north = (north.0.min(n), north.1.max(n));
+        }
+        let origin = (
+            corridor.position.lat * intervals,
+            corridor.position.lon * intervals,
+        );
+        // rows whose cells reach into the extent, the cells span half a
+        // sample to either side
+        let rows = open_range(
+            origin.0 + north.0 / cell.1 - 0.5,
+            origin.0 + north.1 / cell.1 + 0.5,
+        );
+        Scan {
+            intervals,
+            origin,
+            cell,
+            track,
+            length,
+            half_width,
+            east,
+            rows: rows.unwrap_or((0, -1)),
+        }
+    }
+
+    /// Returns the number of cells overlapping the corridor, counting stops
+    /// once it exceeds `limit`.
+    fn count(&self, limit: usize) -> usize {
+        let mut count = 0usize;
+        for gy in self.rows.0..=self.rows.1 {
+            if let Some((first, last)) = self.columns(gy) {
+                let cells = usize::try_from(last.saturating_sub(first)).unwrap_or(usize::MAX);
+                count = count.saturating_add(cells).saturating_add(1);
+            }
+            if count > limit {
+                break;
+            }
+        }
+        count
+    }
+
+    /// Returns the first and last column of the cells in row `gy` overlapping
+    /// the corridor, `None` if there are none.
+    fn columns(&self, gy: i64) -> Option<(i64, i64)> {
+        let (dx, dy) = self.cell;
+        let (sin, cos) = self.track;
+        let north = (gy as f64 - self.origin.0) * dy;
+        // the cell overlaps the rectangle unless separated along one of the
+        // axes of either, with the cell projected onto the axes of the
+        // rectangle as half extents
+        let half_along = (dx * sin.abs() + dy * cos.abs()) / 2.0;
+        let half_across = (dx * cos.abs() + dy * sin.abs()) / 2.0;
+        let mut east = (self.east.0 - dx / 2.0, self.east.1 + dx / 2.0);
+        // along = east * sin + north * cos, across = east * cos - north * sin
+        for (factor, offset, low, high) in [
+            (sin, north * cos, -half_along, self.length + half_along),
+            (
+                cos,
+                -north * sin,
+                -self.half_width - half_across,
+                self.half_width + half_across,
+            ),
+        ] {
+            if factor.abs() < 1e-12 {
+                if offset <= low || offset >= high {
+                    return None;
+                }
+                continue;
+            }
+            let (a, b) = ((low - offset) / factor, (high - offset) / factor);
+            east = (east.0.max(a.min(b)), east.1.min(a.max(b)));
+        }
+        open_range(self.origin.1 + east.0 / dx, self.origin.1 + east.1 / dx)
+    }
+
+    /// Returns the coordinates of a sample.
+    fn coord(&self, gy: i64, gx: i64) -> Coord {
+        let lon = gx as f64 / self.intervals;
+        Coord {
+            lat: gy as f64 / self.intervals,
+            lon: if lon > 180.0 {
+                lon - 360.0
+            } else if lon < -180.0 {
+                lon + 360.0
+            } else {
+                lon
+            },
+        }
+    }
+
+    /// Returns the distance of a sample along and across the track in meters.
+    fn offsets(&self, gy: i64, gx: i64) -> (f64, f64) {
+        let east = (gx as f64 - self.origin.1) * self.cell.0;
+        let north = (gy as f64 - self.origin.0) * self.cell.1;
+        let (sin, cos) = self.track;
+        (east * sin + north * cos, east * cos - north * sin)
+    }
+}
+
+/// Returns the first and last whole number strictly between `low` and `high`.
+fn open_range(low: f64, high: f64) -> Option<(i64, i64)> {
+    let first = floor_f64(low + EPSILON) as i64 + 1;
+    let last = -floor_f64(-(high - EPSILON)) as i64 - 1;
+    (first <= last).then_some((first, last))
+}
@@ -0,0 +1,190 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use super::Coord;
+use crate::{
+    Error,
+    coords::floor_f64,
+    grid::{Grid, Sample},
+};
+
+/// One contour line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contour {
+    /// Elevation of the line in meters.
+    pub level: f64,
+    pub points: Vec<Coord>,
+    /// `true` if the line is a ring; the first point is then repeated at the end.
+    pub closed: bool,
+}
+
+/// Cell edges in the order top, right, bottom, left.
+#[derive(Clone, Copy)]
+enum Edge {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+/// Generates contour lines every `interval` meters, at the levels
+/// `base + n * interval` between the lowest and highest sample of the grid.
+///
+/// The lines are sorted by level. See [`contour_level`] for the details.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the interval is not positive.
+pub fn contours<T: Sample>(
+    grid: &Grid<T>,
+    interval: f64,
+    base: f64,
+) -> Result<Vec<Contour>, Error> {
+    if interval <= 0.0 {
+        return Err(Error::InvalidParameter);
+    }
+    let mut lines = Vec::new();
+    let values = grid.heights[..grid.len()]
+        .iter()
+        .filter_map(|value| value.value());
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    });
+    if min > max {
+        return Ok(lines);
+    }
+    let mut n = floor_f64((min - base) / interval);
+    while base + n * interval <= max {
+        lines.extend(contour_level(grid, base + n * interval));
+        n += 1.0;
+    }
+    Ok(lines)
+}
+
+/// Generates the contour lines of one level with marching squares.
+///
+/// Samples at or above the level count as inside. Crossings are placed by
+/// linear interpolation along the cell edges; saddle cells are resolved with
+/// the mean of their four corners. Cells touching a void sample produce no
+/// segments, so lines end at voids and at the grid border. The segments of
+/// all cells are joined into polylines.
+pub fn contour_level<T: Sample>(grid: &Grid<T>, level: f64) -> Vec<Contour> {
+    // crossing points by edge key, with the up to two segments using them
+    let mut points: BTreeMap<u64, (Coord, Vec<usize>)> = BTreeMap::new();
+    let mut segments: Vec<(u64, u64)> = Vec::new();
+
+    for row in 0..grid.rows.saturating_sub(1) {
+        for col in 0..grid.cols.saturating_sub(1) {
+            let (Some(tl), Some(tr), Some(br), Some(bl)) = (
+                grid.get(row, col),
+                grid.get(row, col + 1),
+                grid.get(row + 1, col + 1),
+                grid.get(row + 1, col),
+            ) else {
+                continue;
+            };
+            let case = (usize::from(tl >= level) << 3)
+                | (usize::from(tr >= level) << 2)
+                | (usize::from(br >= level) << 1)
+                | usize::from(bl >= level);
+            let center_inside = (tl + tr + br + bl) / 4.0 >= level;
+            let pairs: &[(Edge, Edge)] = match case {
+                1 | 14 => &[(Edge::Left, Edge::Bottom)],
+                2 | 13 => &[(Edge::Bottom, Edge::Right)],
+                3 | 12 => &[(Edge::Left, Edge::Right)],
+                4 | 11 => &[(Edge::Top, Edge::Right)],
+                6 | 9 => &[(Edge::Top, Edge::Bottom)],
+                7 | 8 => &[(Edge::Left, Edge::Top)],
+                5 if center_inside => &[(Edge::Left, Edge::Top), (Edge::Bottom, Edge::Right)],
+                5 => &[(Edge::Top, Edge::Right), (Edge::Left, Edge::Bottom)],
+                10 if center_inside => &[(Edge::Top, Edge::Right), (Edge::Left, Edge::Bottom)],
+                10 => &[(Edge::Left, Edge::Top), (Edge::Bottom, Edge::Right)],
+                _ => &[],
+            };
+            for &(a, b) in pairs {
+                let segment = segments.len();
+                let mut key = |edge| {
+                    let (key, coord) = crossing(grid, row, col, edge, level);
+                    points
+                        .entry(key)
+                        .or_insert((coord, Vec::new()))
+                        .1
+                        .push(segment);
+                    key
+                };
+                let (a, b) = (key(a), key(b));
+                segments.push((a, b));
+            }
+        }
+    }
+
+    let mut used = alloc::vec![false; segments.len()];
+    let mut lines = Vec::new();
+    // open lines start at a point used by a single segment, rings anywhere
+    let starts: Vec<usize> = points
+        .values()
+        .filter(|(_, users)| users.len() == 1)
+        .map(|(_, users)| users[0])
+        .chain(0..segments.len())
+        .collect();
+    for segment in starts {
+        if used[segment] {
+            continue;
+        }
+        let (first, _) = segments[segment];
+        let start = if points[&first].1.len() == 1 {
+            first
+        } else {
+            segments[segment].1
+        };
+        let mut line = alloc::vec![points[&start].0];
+        let (mut node, mut current) = (start, segment);
+        loop {
+            used[current] = true;
+            let (a, b) = segments[current];
+            node = if a == node { b } else { a };
+            let (coord, users) = &points[&node];
+            line.push(*coord);
+            match users.iter().find(|user| !used[**user]) {
+                Some(next) => current = *next,
+                None => break,
+            }
+        }
+        lines.push(Contour {
+            level,
+            closed: node == start && line.len() > 2,
+            points: line,
+        });
+    }
+    lines
+}
+
+/// Returns the key and position of the crossing of `level` on an edge of
+/// the cell with north-west corner at `row`, `col`.
+fn crossing<T: Sample>(
+    grid: &Grid<T>,
+    row: usize,
+    col: usize,
+    edge: Edge,
+    level: f64,
+) -> (u64, Coord) {
+    // horizontal edges run east from (r, c), vertical edges south from (r, c)
+    let (r, c, vertical) = match edge {
+        Edge::Top => (row, col, false),
+        Edge::Bottom => (row + 1, col, false),
+        Edge::Left => (row, col, true),
+        Edge::Right => (row, col + 1, true),
+    };
+    let (r2, c2) = if vertical { (r + 1, c) } else { (r, c + 1) };
+    let (a, b) = (
+        grid.get(r, c).unwrap_or(level),
+        grid.get(r2, c2).unwrap_or(level),
+    );
+    let t = if a != b { (level - a) / (b - a) } else { 0.5 };
+    let (from, to) = (grid.coord(r, c), grid.coord(r2, c2));
+    let coord = Coord {
+        lat: from.lat + (to.lat - from.lat) * t,
+        lon: from.lon + (to.lon - from.lon) * t,
+    };
+    let key = ((r * grid.cols + c) as u64) << 1 | u64::from(vertical);
+    (key, coord)
+}
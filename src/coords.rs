@@ -1,8 +1,12 @@
 use core::f64;
+use core::f64::consts::PI;
 
 use core::fmt::Write;
 use heapless::String;
 
+/// Mean earth radius in meters, used for all distance calculations.
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
 /// Represents geographic coordinates (latitude and longitude).
 ///
 /// The struct stores latitude (`lat`) and longitude (`lon`) as floating point numbers.
@@ -60,6 +64,72 @@ impl Coord {
         (lat_trunc, lon_trunc)
     }
 
+    /// Returns the south-west corner of the 1x1 degree tile covering this point.
+    ///
+    /// Unlike [`Coord::trunc`] this rounds towards negative infinity, so
+    /// points on the southern and western hemisphere map to the tile they
+    /// lie in (e.g. -0.5 lies in the tile starting at -1).
+    /// Returns: (latitude of the corner as i8, longitude of the corner as i16)
+    pub fn tile_origin(&self) -> (i8, i16) {
+        (floor_f64(self.lat) as i8, floor_f64(self.lon) as i16)
+    }
+
+    /// Returns the great-circle distance to `other` in meters (haversine formula).
+    pub fn distance_to(&self, other: impl Into<Coord>) -> f64 {
+        let other: Coord = other.into();
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.lon - self.lon).to_radians();
+        let a = libm::sin(d_lat / 2.0) * libm::sin(d_lat / 2.0)
+            + libm::cos(lat1) * libm::cos(lat2) * libm::sin(d_lon / 2.0) * libm::sin(d_lon / 2.0);
+        2.0 * EARTH_RADIUS * libm::atan2(libm::sqrt(a), libm::sqrt(1.0 - a))
+    }
+
+    /// Returns the initial bearing towards `other` in degrees,
+    /// clockwise from north in the range [0, 360).
+    pub fn bearing_to(&self, other: impl Into<Coord>) -> f64 {
+        let other: Coord = other.into();
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lon = (other.lon - self.lon).to_radians();
+        let y = libm::sin(d_lon) * libm::cos(lat2);
+        let x = libm::cos(lat1) * libm::sin(lat2)
+            - libm::sin(lat1) * libm::cos(lat2) * libm::cos(d_lon);
+        (libm::atan2(y, x).to_degrees() + 360.0) % 360.0
+    }
+
+    /// Returns the point reached when travelling `distance` meters along a
+    /// great circle starting with the given bearing (degrees from north).
+    pub fn destination(&self, bearing: f64, distance: f64) -> Coord {
+        let delta = distance / EARTH_RADIUS;
+        let theta = bearing.to_radians();
+        let lat1 = self.lat.to_radians();
+        let lon1 = self.lon.to_radians();
+        let lat2 = libm::asin(
+            libm::sin(lat1) * libm::cos(delta)
+                + libm::cos(lat1) * libm::sin(delta) * libm::cos(theta),
+        );
+        let lon2 = lon1
+            + libm::atan2(
+                libm::sin(theta) * libm::sin(delta) * libm::cos(lat1),
+                libm::cos(delta) - libm::sin(lat1) * libm::sin(lat2),
+            );
+        Coord {
+            lat: lat2.to_degrees(),
+            lon: ((lon2 + 3.0 * PI) % (2.0 * PI) - PI).to_degrees(),
+        }
+    }
+
+    /// Returns the point at `fraction` (0 = self, 1 = other) of the
+    /// great-circle path towards `other`.
+    pub fn interpolate(&self, other: impl Into<Coord>, fraction: f64) -> Coord {
+        let other: Coord = other.into();
+        let distance = self.distance_to(other);
+        if distance == 0.0 {
+            return *self;
+        }
+        self.destination(self.bearing_to(other), distance * fraction)
+    }
+
     /// Returns the filename of the SRTM elevation file covering this point.
     ///
     /// The format is e.g. "N49E008.hgt".
@@ -70,12 +140,14 @@ impl Coord {
     /// let coord = Coord::new(87.235, 10.4234423);
     /// let filename = coord.get_filename();
     /// assert_eq!(filename, "N87E010.hgt");
+    /// let coord = Coord::new(-0.5, -0.5);
+    /// assert_eq!(coord.get_filename(), "S01W001.hgt");
     /// ```
     pub fn get_filename(self) -> String<12> {
+        let (lat, lon) = self.tile_origin();
         // Determine the sign for latitude and longitude
-        let lat_ch = if self.lat >= 0. { 'N' } else { 'S' };
-        let lon_ch = if self.lon >= 0. { 'E' } else { 'W' };
-        let (lat, lon) = self.trunc();
+        let lat_ch = if lat >= 0 { 'N' } else { 'S' };
+        let lon_ch = if lon >= 0 { 'E' } else { 'W' };
         let (lat, lon) = (lat.abs(), lon.abs());
         let mut output = String::<12>::new(); // Maximum length of the filename
         write!(
@@ -95,6 +167,60 @@ impl Coord {
     }
 }
 
+/// Geographic bounding box given by its edges in degrees.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Default)]
+pub struct Bounds {
+    /// Southern edge (minimum latitude).
+    pub south: f64,
+    /// Western edge (minimum longitude).
+    pub west: f64,
+    /// Northern edge (maximum latitude).
+    pub north: f64,
+    /// Eastern edge (maximum longitude).
+    pub east: f64,
+}
+
+impl Bounds {
+    /// Creates a new `Bounds` from its south-west and north-east corners.
+    pub fn new(south_west: impl Into<Coord>, north_east: impl Into<Coord>) -> Self {
+        let (sw, ne): (Coord, Coord) = (south_west.into(), north_east.into());
+        Self {
+            south: sw.lat,
+            west: sw.lon,
+            north: ne.lat,
+            east: ne.lon,
+        }
+    }
+
+    /// Returns `true` if the coordinate lies inside the box, edges included.
+    pub fn contains(&self, coord: impl Into<Coord>) -> bool {
+        let coord: Coord = coord.into();
+        (self.south..=self.north).contains(&coord.lat)
+            && (self.west..=self.east).contains(&coord.lon)
+    }
+}
+
+/// Custom floor function for f64 in no_std environments
+pub(crate) fn floor_f64(x: f64) -> f64 {
+    let i = x as i64;
+    if (i as f64) > x {
+        (i - 1) as f64
+    } else {
+        i as f64
+    }
+}
+
+/// Rounds values off a whole number by no more than a rounding error to it,
+/// e.g. sample positions computed from coordinates on a tile edge.
+pub(crate) fn whole_f64(x: f64) -> f64 {
+    let nearest = crate::tiles::round_f64(x);
+    if (x - nearest).abs() < 1e-9 {
+        nearest
+    } else {
+        x
+    }
+}
+
 /// Allows conversion from a tuple of two f64 values to a `Coord`.
 /// The tuple is expected to contain valid latitude and longitude values.
 impl From<(f64, f64)> for Coord {
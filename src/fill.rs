@@ -0,0 +1,80 @@
+use crate::{
+    Error,
+    grid::{Grid, Sample},
+    heap::CellHeap,
+};
+
+/// Fills the depressions of a grid with the priority-flood algorithm
+/// (Barnes et al. 2014), so that every cell has a path to the border of the
+/// grid that never goes uphill.
+///
+/// The flood starts at the border cells and grows inwards, always from the
+/// lowest cell reached so far. Cells below the water level of the flood are
+/// raised to it; void samples are filled the same way, from the lowest
+/// surrounding terrain. With an `epsilon` above 0 each raised cell is set
+/// `epsilon` meters above the cell it was reached from, so filled areas get
+/// a small gradient towards their outlet and flats drain. Keep `epsilon`
+/// well above the `f32` precision of the heights, e.g. 0.01 m.
+///
+/// `filled` receives the filled heights and `heap` is scratch space; both
+/// must hold `rows * cols` cells. If no border cell holds a valid sample
+/// the flood cannot start and all cells are set to NaN.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - `epsilon` is negative or not finite.
+/// * `Error::BufferTooSmall` - `filled` or `heap` is too small.
+pub fn fill_depressions<T: Sample>(
+    grid: &Grid<T>,
+    epsilon: f32,
+    filled: &mut [f32],
+    heap: &mut [u32],
+) -> Result<(), Error> {
+    if !(epsilon >= 0.0 && epsilon.is_finite()) {
+        return Err(Error::InvalidParameter);
+    }
+    let (rows, cols, len) = (grid.rows, grid.cols, grid.len());
+    if filled.len() < len || heap.len() < len || len > u32::MAX as usize {
+        return Err(Error::BufferTooSmall);
+    }
+    let filled = &mut filled[..len];
+    filled.fill(f32::NAN);
+    let mut heap = CellHeap::new(heap);
+
+    for row in 0..rows {
+        for col in 0..cols {
+            if row != 0 && col != 0 && row + 1 != rows && col + 1 != cols {
+                continue;
+            }
+            if let Some(height) = grid.get(row, col) {
+                let index = row * cols + col;
+                filled[index] = height as f32;
+                heap.push(index, filled);
+            }
+        }
+    }
+
+    // every cell is queued once, so the heap never overflows
+    while let Some(index) = heap.pop(filled) {
+        let level = filled[index];
+        let (row, col) = ((index / cols) as isize, (index % cols) as isize);
+        for d_row in -1..=1 {
+            for d_col in -1..=1 {
+                let (r, c) = (row + d_row, col + d_col);
+                if r < 0 || c < 0 || r >= rows as isize || c >= cols as isize {
+                    continue;
+                }
+                let neighbour = r as usize * cols + c as usize;
+                if !filled[neighbour].is_nan() {
+                    continue;
+                }
+                filled[neighbour] = match grid.heights[neighbour].value() {
+                    Some(height) if height as f32 > level => height as f32,
+                    _ => level + epsilon,
+                };
+                heap.push(neighbour, filled);
+            }
+        }
+    }
+    Ok(())
+}
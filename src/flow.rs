@@ -0,0 +1,211 @@
+use crate::{
+    Error,
+    grid::{Grid, Sample},
+};
+
+// D8 direction codes as used by ESRI and most GIS tools, 0 means undefined.
+pub const EAST: u8 = 1;
+pub const SOUTH_EAST: u8 = 2;
+pub const SOUTH: u8 = 4;
+pub const SOUTH_WEST: u8 = 8;
+pub const WEST: u8 = 16;
+pub const NORTH_WEST: u8 = 32;
+pub const NORTH: u8 = 64;
+pub const NORTH_EAST: u8 = 128;
+
+/// Direction codes with their (row, column) offsets.
+const NEIGHBOURS: [(u8, isize, isize); 8] = [
+    (EAST, 0, 1),
+    (SOUTH_EAST, 1, 1),
+    (SOUTH, 1, 0),
+    (SOUTH_WEST, 1, -1),
+    (WEST, 0, -1),
+    (NORTH_WEST, -1, -1),
+    (NORTH, -1, 0),
+    (NORTH_EAST, -1, 1),
+];
+
+/// Marks cells of the in-degree scratch buffer that are fully accumulated.
+const DONE: u8 = u8::MAX;
+
+/// Returns the (row, column) offset of a direction code,
+/// `None` for 0 and invalid codes.
+pub fn offset(direction: u8) -> Option<(isize, isize)> {
+    NEIGHBOURS
+        .iter()
+        .find(|(code, _, _)| *code == direction)
+        .map(|&(_, d_row, d_col)| (d_row, d_col))
+}
+
+/// Returns the index of the cell a cell drains into,
+/// `None` if the flow leaves the grid or the direction is undefined.
+pub(crate) fn downstream(
+    directions: &[u8],
+    rows: usize,
+    cols: usize,
+    index: usize,
+) -> Option<usize> {
+    let (d_row, d_col) = offset(directions[index])?;
+    let row = (index / cols).checked_add_signed(d_row)?;
+    let col = (index % cols).checked_add_signed(d_col)?;
+    (row < rows && col < cols).then_some(row * cols + col)
+}
+
+/// Computes the D8 flow direction raster of a grid.
+///
+/// Every cell drains to the neighbour with the steepest descent, taking the
+/// longer distance to diagonal neighbours into account. Cells without a lower
+/// neighbour are handled in this order:
+///
+/// * cells on the border of the grid drain outwards, off the grid,
+/// * cells next to a void sample drain into the void,
+/// * cells on a flat drain to an equally high neighbour that already has a
+///   direction, so flats empty towards their outlets,
+/// * all remaining cells are pits and get 0, as do void cells.
+///
+/// `directions` receives the direction codes ([`EAST`], [`SOUTH_EAST`], ...)
+/// and must hold `rows * cols` cells. Pits in SRTM data stop the flow; fill
+/// them first with [`crate::fill::fill_depressions`] for continuous drainage.
+pub fn flow_direction<T: Sample>(grid: &Grid<T>, directions: &mut [u8]) -> Result<(), Error> {
+    if directions.len() < grid.len() {
+        return Err(Error::BufferTooSmall);
+    }
+    let (rows, cols) = (grid.rows as isize, grid.cols as isize);
+    let mut flats = false;
+    for row in 0..grid.rows {
+        let (dx, dy) = grid.cell_size(row);
+        let diagonal = libm::sqrt(dx * dx + dy * dy);
+        for col in 0..grid.cols {
+            let index = row * grid.cols + col;
+            directions[index] = 0;
+            let Some(z) = grid.get(row, col) else {
+                continue;
+            };
+            let (mut steepest, mut next_to_void) = (0.0, 0);
+            for &(code, d_row, d_col) in &NEIGHBOURS {
+                let (r, c) = (row as isize + d_row, col as isize + d_col);
+                if r < 0 || c < 0 || r >= rows || c >= cols {
+                    continue;
+                }
+                let Some(neighbour) = grid.get_signed(r, c) else {
+                    if next_to_void == 0 {
+                        next_to_void = code;
+                    }
+                    continue;
+                };
+                let distance = match (d_row, d_col) {
+                    (0, _) => dx,
+                    (_, 0) => dy,
+                    _ => diagonal,
+                };
+                let drop = (z - neighbour) / distance;
+                if drop > steepest {
+                    (steepest, directions[index]) = (drop, code);
+                }
+            }
+            if directions[index] != 0 {
+                continue;
+            }
+            let border = (
+                if row == 0 {
+                    -1
+                } else {
+                    isize::from(row + 1 == grid.rows)
+                },
+                if col == 0 {
+                    -1
+                } else {
+                    isize::from(col + 1 == grid.cols)
+                },
+            );
+            directions[index] = if border != (0, 0) {
+                NEIGHBOURS
+                    .iter()
+                    .find(|(_, d_row, d_col)| (*d_row, *d_col) == border)
+                    .map_or(0, |(code, _, _)| *code)
+            } else {
+                next_to_void
+            };
+            flats |= directions[index] == 0;
+        }
+    }
+
+    // drain flats towards their outlets until no more cells can be resolved
+    while flats {
+        flats = false;
+        let mut changed = false;
+        for row in 0..grid.rows {
+            for col in 0..grid.cols {
+                let index = row * grid.cols + col;
+                let Some(z) = grid.get(row, col).filter(|_| directions[index] == 0) else {
+                    continue;
+                };
+                let outlet = NEIGHBOURS.iter().find(|&&(_, d_row, d_col)| {
+                    let (r, c) = (row as isize + d_row, col as isize + d_col);
+                    grid.get_signed(r, c) == Some(z) && directions[(r * cols + c) as usize] != 0
+                });
+                match outlet {
+                    Some(&(code, _, _)) => {
+                        directions[index] = code;
+                        changed = true;
+                    }
+                    None => flats = true,
+                }
+            }
+        }
+        flats &= changed;
+    }
+    Ok(())
+}
+
+/// Computes the flow accumulation raster from D8 flow directions.
+///
+/// `accumulation` receives for every cell the number of cells draining
+/// through it, the cell itself included, so streams are the cells above a
+/// threshold. Void cells get 0. `directions` is the output of
+/// [`flow_direction`] for the same grid. `indegree` is scratch space; all
+/// three buffers must hold `rows * cols` cells.
+pub fn flow_accumulation<T: Sample>(
+    grid: &Grid<T>,
+    directions: &[u8],
+    accumulation: &mut [u32],
+    indegree: &mut [u8],
+) -> Result<(), Error> {
+    let (rows, cols, len) = (grid.rows, grid.cols, grid.len());
+    if directions.len() < len || accumulation.len() < len || indegree.len() < len {
+        return Err(Error::BufferTooSmall);
+    }
+    // flow into a void leaves the grid like flow over the border
+    let downstream = |index| {
+        downstream(directions, rows, cols, index)
+            .filter(|next: &usize| grid.heights[*next].value().is_some())
+    };
+    indegree[..len].fill(0);
+    for (index, height) in grid.heights[..len].iter().enumerate() {
+        accumulation[index] = u32::from(height.value().is_some());
+        if let Some(next) = downstream(index) {
+            indegree[next] += 1;
+        }
+    }
+    // pass the counts downstream, starting at cells without inflow and
+    // continuing as long as every inflow of the next cell is known
+    for start in 0..len {
+        if indegree[start] != 0 {
+            continue;
+        }
+        let mut index = start;
+        loop {
+            indegree[index] = DONE;
+            let Some(next) = downstream(index) else {
+                break;
+            };
+            accumulation[next] += accumulation[index];
+            indegree[next] -= 1;
+            if indegree[next] != 0 {
+                break;
+            }
+            index = next;
+        }
+    }
+    Ok(())
+}
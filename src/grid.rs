@@ -0,0 +1,154 @@
+use super::Coord;
+use crate::{ElevationSource, Error, resolutions::Resolution, tiles::VOID_VALUE, tiles::round_f64};
+
+/// Sample is implemented by the value types a [`Grid`] can hold:
+/// heights as read from HGT files (`i16`, void is [`VOID_VALUE`]) and
+/// derived surfaces like filled DEMs (`f32`, void is NaN).
+pub trait Sample: Copy {
+    /// Returns the value in meters, or `None` for void samples.
+    fn value(self) -> Option<f64>;
+}
+
+impl Sample for i16 {
+    fn value(self) -> Option<f64> {
+        (self != VOID_VALUE).then_some(self as f64)
+    }
+}
+
+impl Sample for f32 {
+    fn value(self) -> Option<f64> {
+        (!self.is_nan()).then_some(self as f64)
+    }
+}
+
+/// A rectangular block of samples together with its geographic placement.
+///
+/// Samples are stored row by row, row 0 is the northernmost row and column 0
+/// the westernmost column, like in HGT files and [`crate::Tile::read_window`].
+/// Raster functions of this crate take a `Grid` as input and write their
+/// results into caller buffers of `rows * cols` cells in the same layout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grid<'a, T: Sample = i16> {
+    pub heights: &'a [T],
+    pub rows: usize,
+    pub cols: usize,
+    /// Position of the sample in row 0, column 0.
+    pub origin: Coord,
+    pub resolution: Resolution,
+}
+
+impl<'a> Grid<'a, i16> {
+    /// Reads a window of `rows` x `cols` samples from the source, starting
+    /// at the sample nearest to `coord`, and returns it as a grid.
+    pub fn read<S: ElevationSource>(
+        source: &mut S,
+        coord: impl Into<Coord>,
+        rows: usize,
+        cols: usize,
+        buffer: &'a mut [i16],
+    ) -> Result<Self, Error> {
+        let resolution = source.resolution();
+        let origin = snap(coord.into(), resolution);
+        source.read_window(origin, rows, cols, buffer)?;
+        Grid::new(buffer, rows, cols, origin, resolution)
+    }
+}
+
+impl<'a, T: Sample> Grid<'a, T> {
+    /// Creates a grid over `heights`, which must hold at least `rows * cols` samples.
+    pub fn new(
+        heights: &'a [T],
+        rows: usize,
+        cols: usize,
+        origin: impl Into<Coord>,
+        resolution: Resolution,
+    ) -> Result<Self, Error> {
+        if rows.checked_mul(cols).is_none_or(|len| heights.len() < len) {
+            return Err(Error::BufferTooSmall);
+        }
+        Ok(Grid {
+            heights,
+            rows,
+            cols,
+            origin: origin.into(),
+            resolution,
+        })
+    }
+
+    /// Returns the number of cells, `rows * cols`.
+    pub fn len(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    /// Returns `true` if the grid has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value of a cell in meters,
+    /// `None` for void samples and positions outside the grid.
+    pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        self.heights[row * self.cols + col].value()
+    }
+
+    /// Returns the value at a signed position, see [`Grid::get`].
+    pub fn get_signed(&self, row: isize, col: isize) -> Option<f64> {
+        if row < 0 || col < 0 {
+            return None;
+        }
+        self.get(row as usize, col as usize)
+    }
+
+    /// Returns the coordinate of a cell.
+    pub fn coord(&self, row: usize, col: usize) -> Coord {
+        let step = self.resolution.step();
+        Coord {
+            lat: self.origin.lat - row as f64 * step,
+            lon: self.origin.lon + col as f64 * step,
+        }
+    }
+
+    /// Returns the (row, column) of the cell nearest to the coordinate,
+    /// or `None` if it lies outside the grid.
+    pub fn index_of(&self, coord: impl Into<Coord>) -> Option<(usize, usize)> {
+        let coord: Coord = coord.into();
+        let step = self.resolution.step();
+        let row = round_f64((self.origin.lat - coord.lat) / step);
+        let col = round_f64((coord.lon - self.origin.lon) / step);
+        if row < 0.0 || col < 0.0 || row >= self.rows as f64 || col >= self.cols as f64 {
+            return None;
+        }
+        Some((row as usize, col as usize))
+    }
+
+    /// Returns the size of the cells of a row in meters as (east-west, north-south).
+    pub fn cell_size(&self, row: usize) -> (f64, f64) {
+        self.resolution.cell_size(self.coord(row, 0).lat)
+    }
+
+    /// Returns the 3x3 neighbourhood of a cell row by row, starting in the
+    /// north-west. Returns `None` at the border of the grid or if any of the
+    /// nine samples is void.
+    pub fn neighbourhood(&self, row: usize, col: usize) -> Option<[f64; 9]> {
+        if row == 0 || col == 0 || row + 1 >= self.rows || col + 1 >= self.cols {
+            return None;
+        }
+        let mut z = [0.0; 9];
+        for (i, value) in z.iter_mut().enumerate() {
+            *value = self.get(row + i / 3 - 1, col + i % 3 - 1)?;
+        }
+        Some(z)
+    }
+}
+
+/// Moves a coordinate onto the nearest sample position of the resolution.
+pub fn snap(coord: Coord, resolution: Resolution) -> Coord {
+    let intervals = (resolution.point_per_degree() - 1) as f64;
+    Coord {
+        lat: round_f64(coord.lat * intervals) / intervals,
+        lon: round_f64(coord.lon * intervals) / intervals,
+    }
+}
@@ -0,0 +1,64 @@
+/// Binary min-heap of cell indices ordered by a key per cell, stored in a
+/// caller provided buffer so raster algorithms can run without an allocator.
+///
+/// The keys live outside the heap and must not change while a cell is queued.
+pub(crate) struct CellHeap<'a> {
+    cells: &'a mut [u32],
+    len: usize,
+}
+
+impl<'a> CellHeap<'a> {
+    pub(crate) fn new(cells: &'a mut [u32]) -> Self {
+        CellHeap { cells, len: 0 }
+    }
+
+    /// Adds a cell, returns `false` if the buffer is full.
+    pub(crate) fn push(&mut self, cell: usize, keys: &[f32]) -> bool {
+        if self.len == self.cells.len() {
+            return false;
+        }
+        let mut i = self.len;
+        self.cells[i] = cell as u32;
+        self.len += 1;
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if !self.less(i, parent, keys) {
+                break;
+            }
+            self.cells.swap(i, parent);
+            i = parent;
+        }
+        true
+    }
+
+    /// Removes and returns the cell with the lowest key.
+    pub(crate) fn pop(&mut self, keys: &[f32]) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let top = self.cells[0] as usize;
+        self.len -= 1;
+        self.cells[0] = self.cells[self.len];
+        let mut i = 0;
+        loop {
+            let (left, right) = (2 * i + 1, 2 * i + 2);
+            let mut smallest = i;
+            if left < self.len && self.less(left, smallest, keys) {
+                smallest = left;
+            }
+            if right < self.len && self.less(right, smallest, keys) {
+                smallest = right;
+            }
+            if smallest == i {
+                break;
+            }
+            self.cells.swap(i, smallest);
+            i = smallest;
+        }
+        Some(top)
+    }
+
+    fn less(&self, a: usize, b: usize, keys: &[f32]) -> bool {
+        keys[self.cells[a] as usize] < keys[self.cells[b] as usize]
+    }
+}
@@ -0,0 +1,131 @@
+use super::Coord;
+use crate::{Bounds, ElevationSource, Error, coords::EARTH_RADIUS, terrain::horn_gradient};
+
+/// Azimuths of the light sources used for multi-directional hillshade.
+const MULTI_AZIMUTHS: [f64; 4] = [225.0, 270.0, 315.0, 360.0];
+
+/// Parameters of [`hillshade`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HillshadeOptions {
+    /// Direction of the light source in degrees clockwise from north.
+    pub azimuth: f64,
+    /// Angle of the light source above the horizon in degrees.
+    pub altitude: f64,
+    /// Vertical exaggeration applied to the heights.
+    pub z_factor: f64,
+    /// Blends light from 225°, 270°, 315° and 360°, weighted by the aspect
+    /// of each cell, instead of using `azimuth`.
+    pub multidirectional: bool,
+}
+
+impl Default for HillshadeOptions {
+    /// Light from the north-west at 45°, no exaggeration.
+    fn default() -> Self {
+        HillshadeOptions {
+            azimuth: 315.0,
+            altitude: 45.0,
+            z_factor: 1.0,
+            multidirectional: false,
+        }
+    }
+}
+
+/// Returns the illumination (0 to 1) of a surface with the given height
+/// gradient (towards east, towards north) in meters per meter.
+pub fn shade(gradient: (f64, f64), options: &HillshadeOptions) -> f64 {
+    let (dz_dx, dz_dy) = (gradient.0 * options.z_factor, gradient.1 * options.z_factor);
+    if !options.multidirectional {
+        return directional(dz_dx, dz_dy, options.azimuth, options.altitude);
+    }
+    let aspect = libm::atan2(-dz_dx, -dz_dy);
+    // the four weights sin²(aspect - azimuth) always add up to 2
+    MULTI_AZIMUTHS
+        .iter()
+        .map(|azimuth| {
+            let weight = libm::sin(aspect - azimuth.to_radians());
+            weight * weight * directional(dz_dx, dz_dy, *azimuth, options.altitude)
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Illumination by a single light source, the cosine between surface normal and sun.
+fn directional(dz_dx: f64, dz_dy: f64, azimuth: f64, altitude: f64) -> f64 {
+    let (azimuth, altitude) = (azimuth.to_radians(), altitude.to_radians());
+    let sun = (
+        libm::sin(azimuth) * libm::cos(altitude),
+        libm::cos(azimuth) * libm::cos(altitude),
+        libm::sin(altitude),
+    );
+    let norm = libm::sqrt(dz_dx * dz_dx + dz_dy * dz_dy + 1.0);
+    let value = (-dz_dx * sun.0 - dz_dy * sun.1 + sun.2) / norm;
+    value.max(0.0)
+}
+
+/// Renders a shaded relief of the bounding box.
+///
+/// The box is divided into `width` x `height` pixels, written row by row from
+/// the north-west into `out` as intensities from 0 (dark) to 255 (fully lit).
+/// Each pixel is shaded from the gradient of the interpolated heights around
+/// its center, sampled at the pixel size but never closer than the DEM
+/// resolution. Pixels next to void samples are set to 0.
+///
+/// Every pixel needs nine height lookups, wrapping the reader in a
+/// [`crate::CachedReader`] avoids most of the file accesses.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the box or the output size is empty.
+/// * `Error::BufferTooSmall` - `out` holds less than `width * height` pixels.
+/// * any error of the source except `Error::InvalidData`.
+pub fn hillshade<S: ElevationSource>(
+    source: &mut S,
+    bounds: Bounds,
+    width: usize,
+    height: usize,
+    options: &HillshadeOptions,
+    out: &mut [u8],
+) -> Result<(), Error> {
+    if width == 0 || height == 0 || bounds.north <= bounds.south || bounds.east <= bounds.west {
+        return Err(Error::InvalidParameter);
+    }
+    if width.checked_mul(height).is_none_or(|len| out.len() < len) {
+        return Err(Error::BufferTooSmall);
+    }
+    let step = source.resolution().step();
+    let pixel_lat = (bounds.north - bounds.south) / height as f64;
+    let pixel_lon = (bounds.east - bounds.west) / width as f64;
+    let (d_lat, d_lon) = (pixel_lat.max(step), pixel_lon.max(step));
+
+    for y in 0..height {
+        let lat = bounds.north - (y as f64 + 0.5) * pixel_lat;
+        let dy = EARTH_RADIUS * d_lat.to_radians();
+        let dx = EARTH_RADIUS * d_lon.to_radians() * libm::cos(lat.to_radians());
+        for x in 0..width {
+            let lon = bounds.west + (x as f64 + 0.5) * pixel_lon;
+            let mut z = [0.0; 9];
+            let mut valid = true;
+            for (i, value) in z.iter_mut().enumerate() {
+                let coord = Coord {
+                    lat: lat + (1.0 - (i / 3) as f64) * d_lat,
+                    lon: lon + ((i % 3) as f64 - 1.0) * d_lon,
+                };
+                match source.interpolated_height(coord) {
+                    Ok(height) => *value = height,
+                    Err(Error::InvalidData) => {
+                        valid = false;
+                        break;
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            out[y * width + x] = if valid {
+                let intensity = shade(horn_gradient(&z, dx, dy), options) * 255.0;
+                (intensity + 0.5) as u8
+            } else {
+                0
+            };
+        }
+    }
+    Ok(())
+}
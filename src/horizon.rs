@@ -0,0 +1,135 @@
+use crate::{
+    ElevationSource, Error,
+    los::{Position, STANDARD_K_FACTOR, curvature_drop},
+};
+
+/// Parameters of [`horizon`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HorizonOptions {
+    /// Distance in meters up to which the terrain is scanned.
+    pub max_distance: f64,
+    /// Take the curvature of the earth into account.
+    pub earth_curvature: bool,
+    /// Refraction coefficient, only used together with `earth_curvature`.
+    pub k_factor: f64,
+    /// Distance between terrain samples along each azimuth in meters,
+    /// `None` uses the cell size of the source.
+    pub spacing: Option<f64>,
+}
+
+impl Default for HorizonOptions {
+    /// 20 km with earth curvature and standard refraction, sampling at the cell size.
+    fn default() -> Self {
+        HorizonOptions {
+            max_distance: 20_000.0,
+            earth_curvature: true,
+            k_factor: STANDARD_K_FACTOR,
+            spacing: None,
+        }
+    }
+}
+
+/// Returns the elevation angle of the terrain horizon in one direction.
+///
+/// The terrain is sampled with interpolated heights along the great circle
+/// starting at the observer in direction `azimuth` (degrees clockwise from
+/// north), and the steepest angle from the observer's eye to a sample is
+/// the horizon, in degrees above the horizontal plane. It is negative if the
+/// terrain falls away, e.g. on a summit. Returns `None` if all samples are
+/// void.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the distance or spacing is not positive and finite.
+/// * `Error::InvalidData` - the terrain at an `AboveGround` observer is void.
+/// * any error of the source except `Error::InvalidData`.
+pub fn horizon_elevation<S: ElevationSource>(
+    source: &mut S,
+    observer: Position,
+    azimuth: f64,
+    options: &HorizonOptions,
+) -> Result<Option<f64>, Error> {
+    let spacing = spacing(source, &observer, options)?;
+    let eye = observer.height(source)?;
+    scan(source, &observer, eye, azimuth, spacing, options)
+}
+
+/// Computes the horizon around an observer in equal azimuth bins.
+///
+/// `out` is divided into bins of `360 / out.len()` degrees, bin `i` is scanned
+/// at the azimuth `i * 360 / out.len()` and receives its elevation angle in
+/// degrees as described for [`horizon_elevation`], NaN if all samples are
+/// void. The cost grows with the number of bins times `max_distance / spacing`;
+/// wrapping the reader in a [`crate::CachedReader`] helps a lot.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - `out` is empty, the distance or spacing is not positive and finite.
+/// * `Error::InvalidData` - the terrain at an `AboveGround` observer is void.
+/// * any error of the source except `Error::InvalidData`.
+pub fn horizon<S: ElevationSource>(
+    source: &mut S,
+    observer: Position,
+    options: &HorizonOptions,
+    out: &mut [f32],
+) -> Result<(), Error> {
+    if out.is_empty() {
+        return Err(Error::InvalidParameter);
+    }
+    let spacing = spacing(source, &observer, options)?;
+    let eye = observer.height(source)?;
+    let bins = out.len();
+    for (i, value) in out.iter_mut().enumerate() {
+        let azimuth = i as f64 * 360.0 / bins as f64;
+        *value = scan(source, &observer, eye, azimuth, spacing, options)?
+            .map_or(f32::NAN, |elevation| elevation as f32);
+    }
+    Ok(())
+}
+
+/// Checks the options and returns the sample spacing.
+fn spacing<S: ElevationSource>(
+    source: &S,
+    observer: &Position,
+    options: &HorizonOptions,
+) -> Result<f64, Error> {
+    let spacing = options
+        .spacing
+        .unwrap_or_else(|| source.resolution().cell_size(observer.coord.lat).1);
+    if !spacing.is_finite()
+        || spacing <= 0.0
+        || !options.max_distance.is_finite()
+        || options.max_distance <= 0.0
+    {
+        return Err(Error::InvalidParameter);
+    }
+    Ok(spacing)
+}
+
+/// Scans one azimuth from an eye at `eye` meters above mean sea level.
+fn scan<S: ElevationSource>(
+    source: &mut S,
+    observer: &Position,
+    eye: f64,
+    azimuth: f64,
+    spacing: f64,
+    options: &HorizonOptions,
+) -> Result<Option<f64>, Error> {
+    let mut steepest: Option<f64> = None;
+    let steps = (options.max_distance / spacing) as usize;
+    for i in 1..=steps {
+        let distance = i as f64 * spacing;
+        let coord = observer.coord.destination(azimuth, distance);
+        let mut terrain = match source.interpolated_height(coord) {
+            Ok(height) => height,
+            Err(Error::InvalidData) => continue,
+            Err(err) => return Err(err),
+        };
+        if options.earth_curvature {
+            terrain -= curvature_drop(distance, options.k_factor);
+        }
+        let slope = (terrain - eye) / distance;
+        steepest = Some(steepest.map_or(slope, |steepest| steepest.max(slope)));
+    }
+    Ok(steepest.map(|slope| libm::atan(slope).to_degrees()))
+}
@@ -0,0 +1,154 @@
+use crate::{
+    Error,
+    bitmap::{bitmap_len, get_bit},
+    grid::{Grid, Sample},
+    skyview::{SkyViewOptions, sky_view_grid},
+    solar::{SolarPosition, shadow_mask, solar_position},
+    terrain::slope_aspect_at,
+};
+
+/// Solar constant in W/m².
+pub const SOLAR_CONSTANT: f64 = 1361.0;
+
+/// Unix time of the perihelion in early January 2000.
+const PERIHELION: i64 = 946_857_600;
+
+/// Sky view scan for the diffuse radiation, limited only by the grid.
+const SKY_VIEW: SkyViewOptions = SkyViewOptions {
+    directions: 16,
+    max_distance: f64::INFINITY,
+};
+
+/// Parameters of [`irradiance`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IrradianceOptions {
+    /// Start of the period as Unix time in UTC, usually midnight of the first day.
+    pub start: i64,
+    /// Length of the period in days.
+    pub days: u32,
+    /// Only every `day_step`th day is computed and counted for the days
+    /// skipped, e.g. 7 for a quick annual estimate.
+    pub day_step: u32,
+    /// Seconds between the sun positions within a day, must divide 86400.
+    pub time_step: i64,
+    /// Fraction of the direct radiation passing the atmosphere with the sun
+    /// in the zenith, 0.7 for a clear sky.
+    pub transmittance: f64,
+    /// Fraction of the global radiation that is diffuse, 0.3 for a clear sky.
+    pub diffuse_proportion: f64,
+}
+
+impl Default for IrradianceOptions {
+    /// One clear day starting at 2024-06-21 00:00 UTC, sampled every 30 minutes.
+    fn default() -> Self {
+        IrradianceOptions {
+            start: 1_718_928_000,
+            days: 1,
+            day_step: 1,
+            time_step: 1800,
+            transmittance: 0.7,
+            diffuse_proportion: 0.3,
+        }
+    }
+}
+
+/// Computes the clear-sky solar radiation received per cell over a period.
+///
+/// For every time step the sun position at the center of the grid is
+/// computed and the cast shadows are found with [`shadow_mask`]. Each cell
+/// receives the direct radiation on its surface, tilted by Horn's slope and
+/// aspect, unless shadowed, plus isotropic diffuse radiation weighted by its
+/// sky view factor from [`crate::skyview::sky_view_at`]. The atmosphere is
+/// modelled with a constant transmittance and the air mass of Kasten and Young.
+///
+/// `out` receives the energy in Wh/m², border cells and cells next to void
+/// samples are set to NaN. `sky_view` receives the sky view factors, scanned
+/// in 16 directions up to the grid border, and must hold `rows * cols` cells;
+/// `shadow` is scratch space of [`crate::bitmap::bitmap_len`] bytes. To
+/// include shadows cast from outside a bounding box, read the grid with a
+/// margin.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the period, a step or an atmosphere parameter is out of range.
+/// * `Error::BufferTooSmall` - a buffer is too small.
+pub fn irradiance<T: Sample>(
+    grid: &Grid<T>,
+    options: &IrradianceOptions,
+    out: &mut [f32],
+    sky_view: &mut [f32],
+    shadow: &mut [u8],
+) -> Result<(), Error> {
+    if options.days == 0
+        || options.day_step == 0
+        || options.time_step <= 0
+        || 86_400 % options.time_step != 0
+        || !(options.transmittance > 0.0 && options.transmittance <= 1.0)
+        || !(options.diffuse_proportion >= 0.0 && options.diffuse_proportion < 1.0)
+    {
+        return Err(Error::InvalidParameter);
+    }
+    let len = grid.len();
+    if out.len() < len || sky_view.len() < len || shadow.len() < bitmap_len(grid.rows, grid.cols) {
+        return Err(Error::BufferTooSmall);
+    }
+    for row in 0..grid.rows {
+        for col in 0..grid.cols {
+            let defined = slope_aspect_at(grid, row, col).is_some();
+            out[row * grid.cols + col] = if defined { 0.0 } else { f32::NAN };
+        }
+    }
+    sky_view_grid(grid, &SKY_VIEW, sky_view)?;
+
+    let center = grid.coord(grid.rows / 2, grid.cols / 2);
+    for day in (0..options.days).step_by(options.day_step as usize) {
+        let weight = options.day_step.min(options.days - day) as f64;
+        let hours = weight * options.time_step as f64 / 3600.0;
+        let midnight = options.start + day as i64 * 86_400;
+        for step in 0..86_400 / options.time_step {
+            let time = midnight + step * options.time_step + options.time_step / 2;
+            let sun = solar_position(time, center);
+            if !sun.is_up() {
+                continue;
+            }
+            shadow_mask(grid, &sun, shadow)?;
+            let normal = direct_normal(&sun, time, options.transmittance);
+            let diffuse = normal * options.diffuse_proportion / (1.0 - options.diffuse_proportion)
+                * libm::sin(sun.elevation.to_radians());
+            for row in 0..grid.rows {
+                for col in 0..grid.cols {
+                    let Some(surface) = slope_aspect_at(grid, row, col) else {
+                        continue;
+                    };
+                    let index = row * grid.cols + col;
+                    let mut power = diffuse * sky_view[index] as f64;
+                    if !get_bit(shadow, index) {
+                        power += normal * incidence(&sun, surface.slope, surface.aspect);
+                    }
+                    out[index] += (power * hours) as f32;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Direct radiation on a surface facing the sun in W/m².
+fn direct_normal(sun: &SolarPosition, time: i64, transmittance: f64) -> f64 {
+    let days = (time - PERIHELION) as f64 / 86_400.0;
+    let distance = 1.0 + 0.033 * libm::cos(2.0 * core::f64::consts::PI * days / 365.25);
+    let air_mass = 1.0
+        / (libm::sin(sun.elevation.to_radians())
+            + 0.50572 * libm::pow(sun.elevation + 6.07995, -1.6364));
+    SOLAR_CONSTANT * distance * libm::pow(transmittance, air_mass)
+}
+
+/// Cosine of the angle between the sun and the normal of a surface,
+/// 0 if the sun is behind the surface.
+fn incidence(sun: &SolarPosition, slope: f64, aspect: Option<f64>) -> f64 {
+    let (zenith, slope) = ((90.0 - sun.elevation).to_radians(), slope.to_radians());
+    let facing = aspect.map_or(0.0, |aspect| {
+        libm::cos((sun.azimuth - aspect).to_radians()) * libm::sin(zenith) * libm::sin(slope)
+    });
+    (libm::cos(zenith) * libm::cos(slope) + facing).max(0.0)
+}
@@ -1,11 +1,43 @@
 #![no_std]
-pub use coords::Coord;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub use cache::CachedReader;
+pub use coords::{Bounds, Coord};
+pub use grid::Grid;
+pub use memory::MemoryReader;
+pub use mosaic::Mosaic;
 pub use resolutions::Resolution;
 pub use tiles::Tile;
 
+pub mod bitmap;
+pub mod cache;
+pub mod clearance;
+#[cfg(feature = "alloc")]
+pub mod contours;
 pub mod coords;
+pub mod fill;
+pub mod flow;
+pub mod grid;
+mod heap;
+pub mod hillshade;
+pub mod horizon;
+pub mod irradiance;
+pub mod los;
+pub mod memory;
+pub mod mosaic;
+pub mod peaks;
+pub mod profile;
+pub mod radio;
+pub mod resample;
 pub mod resolutions;
+pub mod skyview;
+pub mod solar;
+pub mod terrain;
 pub mod tiles;
+pub mod track;
+pub mod viewshed;
+pub mod watershed;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
@@ -17,6 +49,8 @@ pub enum Error {
     FileRead,
     IndexOutOfBounds,
     InvalidData,
+    BufferTooSmall,
+    InvalidParameter,
 }
 
 /// HgtReader is a trait for reading SRTM elevation data.
@@ -43,8 +77,94 @@ pub trait HgtReader {
     /// otherwise returns an `Error`.
     fn read_hgt_data(&mut self, pos: u64, buffer: &mut [u8; 2]) -> Result<(), Error>;
 
+    /// Reads a run of consecutive samples starting at the given position.
+    /// The position is given in bytes.
+    /// The buffer is filled completely; its length must be a multiple of 2,
+    /// otherwise `Error::InvalidParameter` is returned. The default implementation calls [`HgtReader::read_hgt_data`] once per
+    /// sample, readers that can do a single bulk read should override it.
+    /// Returns `Ok(())` if the data is successfully read,
+    /// otherwise returns an `Error`.
+    fn read_hgt_block(&mut self, pos: u64, buffer: &mut [u8]) -> Result<(), Error> {
+        if !buffer.len().is_multiple_of(2) {
+            return Err(Error::InvalidParameter);
+        }
+        let mut sample = [0; 2];
+        for (i, chunk) in buffer.chunks_exact_mut(2).enumerate() {
+            self.read_hgt_data(pos + (i * 2) as u64, &mut sample)?;
+            chunk.copy_from_slice(&sample);
+        }
+        Ok(())
+    }
+
     /// Closes the file.
     /// Returns `Ok(())` if the file is successfully closed,
     /// otherwise returns an `Error`.
     fn close_hgt_file(&mut self) -> Result<(), Error>;
 }
+
+/// ElevationSource is implemented by everything that can answer height
+/// queries for arbitrary coordinates, like [`Tile`] and [`Mosaic`].
+/// Terrain analysis functions of this crate are generic over it.
+pub trait ElevationSource {
+    /// Returns the resolution of the underlying HGT data.
+    fn resolution(&self) -> Resolution;
+
+    /// Returns the height of the sample nearest to the coordinate.
+    /// Returns `Err(Error::InvalidData)` for void samples.
+    fn height_at(&mut self, coord: Coord) -> Result<i16, Error>;
+
+    /// Reads a rectangular window of heights whose north-west corner is the
+    /// sample nearest to `coord`, see [`Tile::read_window`].
+    fn read_window(
+        &mut self,
+        coord: Coord,
+        rows: usize,
+        cols: usize,
+        buffer: &mut [i16],
+    ) -> Result<(), Error>;
+
+    /// Returns the bilinearly interpolated height at the coordinate.
+    ///
+    /// The four samples surrounding the coordinate are fetched with
+    /// [`ElevationSource::height_at`]. Void samples are left out and the
+    /// remaining weights renormalized; if all four are void,
+    /// `Err(Error::InvalidData)` is returned.
+    fn interpolated_height(&mut self, coord: Coord) -> Result<f64, Error> {
+        let step = self.resolution().step();
+        // positions on a sample row or column must not give a tiny weight to
+        // the neighbouring one, which may lie in a tile that is not available
+        let y = coords::whole_f64(coord.lat / step);
+        let x = coords::whole_f64(coord.lon / step);
+        let (y0, x0) = (coords::floor_f64(y), coords::floor_f64(x));
+        let (fy, fx) = (y - y0, x - x0);
+        let mut sum = 0.0;
+        let mut weights = 0.0;
+        for (dy, dx, weight) in [
+            (0.0, 0.0, (1.0 - fy) * (1.0 - fx)),
+            (0.0, 1.0, (1.0 - fy) * fx),
+            (1.0, 0.0, fy * (1.0 - fx)),
+            (1.0, 1.0, fy * fx),
+        ] {
+            if weight <= 0.0 {
+                continue;
+            }
+            let corner = Coord {
+                lat: (y0 + dy) * step,
+                lon: (x0 + dx) * step,
+            };
+            match self.height_at(corner) {
+                Ok(height) => {
+                    sum += height as f64 * weight;
+                    weights += weight;
+                }
+                Err(Error::InvalidData) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        if weights > 0.0 {
+            Ok(sum / weights)
+        } else {
+            Err(Error::InvalidData)
+        }
+    }
+}
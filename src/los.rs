@@ -0,0 +1,166 @@
+use super::Coord;
+use crate::{ElevationSource, Error, coords::EARTH_RADIUS};
+
+/// Standard refraction coefficient for radio and optical paths.
+pub const STANDARD_K_FACTOR: f64 = 4.0 / 3.0;
+
+/// Height of a point, either relative to the terrain or absolute.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Altitude {
+    /// Meters above the terrain at the point.
+    AboveGround(f64),
+    /// Meters above mean sea level.
+    AboveSeaLevel(f64),
+}
+
+/// A point in space given by its coordinate and altitude.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub coord: Coord,
+    pub altitude: Altitude,
+}
+
+impl Position {
+    /// Creates a position `height` meters above the terrain.
+    pub fn above_ground(coord: impl Into<Coord>, height: f64) -> Self {
+        Position {
+            coord: coord.into(),
+            altitude: Altitude::AboveGround(height),
+        }
+    }
+
+    /// Creates a position `height` meters above mean sea level.
+    pub fn above_sea_level(coord: impl Into<Coord>, height: f64) -> Self {
+        Position {
+            coord: coord.into(),
+            altitude: Altitude::AboveSeaLevel(height),
+        }
+    }
+
+    /// Returns the height above mean sea level,
+    /// looking up the terrain height if necessary.
+    pub fn height<S: ElevationSource>(&self, source: &mut S) -> Result<f64, Error> {
+        match self.altitude {
+            Altitude::AboveSeaLevel(height) => Ok(height),
+            Altitude::AboveGround(height) => Ok(source.interpolated_height(self.coord)? + height),
+        }
+    }
+}
+
+/// Parameters of [`line_of_sight`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LosOptions {
+    /// Take the curvature of the earth into account.
+    pub earth_curvature: bool,
+    /// Refraction coefficient, the earth radius is multiplied by it.
+    /// Only used together with `earth_curvature`.
+    pub k_factor: f64,
+    /// Distance between terrain samples in meters,
+    /// `None` uses the cell size of the source.
+    pub spacing: Option<f64>,
+}
+
+impl Default for LosOptions {
+    /// Earth curvature with standard refraction, sampling at the cell size.
+    fn default() -> Self {
+        LosOptions {
+            earth_curvature: true,
+            k_factor: STANDARD_K_FACTOR,
+            spacing: None,
+        }
+    }
+}
+
+/// The terrain point blocking a line of sight.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obstruction {
+    pub coord: Coord,
+    /// Distance from the start in meters.
+    pub distance: f64,
+    /// Terrain height above mean sea level, including the earth bulge
+    /// if curvature is applied.
+    pub terrain_height: f64,
+    /// Height of the sight line above mean sea level at this point.
+    pub line_height: f64,
+}
+
+/// Result of [`line_of_sight`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineOfSight {
+    Visible,
+    /// Blocked, with the obstruction closest to the start.
+    Blocked(Obstruction),
+}
+
+impl LineOfSight {
+    /// Returns `true` if nothing blocks the line of sight.
+    pub fn is_visible(&self) -> bool {
+        matches!(self, LineOfSight::Visible)
+    }
+}
+
+/// Returns how far the earth rises above the straight line between two
+/// points on the surface, at a point `d1` meters from the one and `d2` meters
+/// from the other end.
+pub fn earth_bulge(d1: f64, d2: f64, k_factor: f64) -> f64 {
+    d1 * d2 / (2.0 * k_factor * EARTH_RADIUS)
+}
+
+/// Returns how far the surface drops below the horizontal plane of an
+/// observer at the given distance.
+pub fn curvature_drop(distance: f64, k_factor: f64) -> f64 {
+    distance * distance / (2.0 * k_factor * EARTH_RADIUS)
+}
+
+/// Checks whether the terrain blocks the straight line between two positions.
+///
+/// The terrain is sampled along the great circle between both positions
+/// using interpolated heights. Void samples never block.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the spacing is not positive.
+/// * `Error::InvalidData` - the terrain height of an `AboveGround` position is void.
+/// * any error of the source.
+pub fn line_of_sight<S: ElevationSource>(
+    source: &mut S,
+    from: Position,
+    to: Position,
+    options: &LosOptions,
+) -> Result<LineOfSight, Error> {
+    let spacing = options
+        .spacing
+        .unwrap_or_else(|| source.resolution().cell_size(from.coord.lat).1);
+    if spacing <= 0.0 {
+        return Err(Error::InvalidParameter);
+    }
+    let (start, end) = (from.height(source)?, to.height(source)?);
+    let length = from.coord.distance_to(to.coord);
+    let steps = (length / spacing) as usize;
+
+    for i in 1..=steps {
+        let distance = i as f64 * spacing;
+        if distance >= length {
+            break;
+        }
+        let coord = from.coord.interpolate(to.coord, distance / length);
+        let mut terrain = match source.interpolated_height(coord) {
+            Ok(height) => height,
+            Err(Error::InvalidData) => continue,
+            Err(err) => return Err(err),
+        };
+        if options.earth_curvature {
+            terrain += earth_bulge(distance, length - distance, options.k_factor);
+        }
+        let line_height = start + (end - start) * distance / length;
+        if terrain > line_height {
+            return Ok(LineOfSight::Blocked(Obstruction {
+                coord,
+                distance,
+                terrain_height: terrain,
+                line_height,
+            }));
+        }
+    }
+    Ok(LineOfSight::Visible)
+}
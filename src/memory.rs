@@ -0,0 +1,102 @@
+use heapless::Vec;
+
+use super::Coord;
+use crate::{Error, HgtReader};
+
+/// MemoryReader is an [`HgtReader`] serving HGT files that are already in
+/// memory, e.g. in RAM or embedded into flash with `include_bytes!`.
+///
+/// Up to `N` tiles are registered with [`MemoryReader::add_tile`], keyed by
+/// the south-west corner of the tile. Opening a file selects the matching
+/// tile, no filesystem is involved.
+///
+/// # Example
+/// ```ignore
+/// static N49E008: &[u8] = include_bytes!("N49E008.hgt");
+/// let mut reader = MemoryReader::<1>::new();
+/// reader.add_tile(49, 8, N49E008).ok();
+/// let mut tile = Tile::<MemoryReader<1>>::new(Resolution::SRTM3, reader);
+/// let height = tile.get_height::<MemoryReader<1>>((49.1, 8.2));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryReader<'a, const N: usize> {
+    tiles: Vec<((i8, i16), &'a [u8]), N>,
+    /// Index of the open tile.
+    open: Option<usize>,
+}
+
+impl<'a, const N: usize> MemoryReader<'a, N> {
+    /// Creates a reader without any tiles.
+    pub fn new() -> Self {
+        MemoryReader {
+            tiles: Vec::new(),
+            open: None,
+        }
+    }
+
+    /// Registers the content of the HGT file whose south-west corner is at
+    /// `latitude`, `longitude`. A tile registered twice replaces the first one.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::InvalidParameter` - the corner is not the origin of a tile, -90 to 89 degrees latitude and -180 to 179 degrees longitude.
+    /// * `Error::BufferTooSmall` - the reader already holds `N` tiles.
+    pub fn add_tile(&mut self, latitude: i8, longitude: i16, data: &'a [u8]) -> Result<(), Error> {
+        if !(-90..90).contains(&latitude) || !(-180..180).contains(&longitude) {
+            return Err(Error::InvalidParameter);
+        }
+        let key = (latitude, longitude);
+        if let Some(tile) = self.tiles.iter_mut().find(|(origin, _)| *origin == key) {
+            tile.1 = data;
+            return Ok(());
+        }
+        self.tiles
+            .push((key, data))
+            .map_err(|_| Error::BufferTooSmall)
+    }
+
+    /// Returns the data of the open tile.
+    fn data(&self) -> Result<&'a [u8], Error> {
+        self.open
+            .map(|index| self.tiles[index].1)
+            .ok_or(Error::NotFound)
+    }
+}
+
+impl<const N: usize> HgtReader for MemoryReader<'_, N> {
+    /// Selects the registered tile with the given file name.
+    fn open_hgt_file(&mut self, file_name: &str) -> Result<(), Error> {
+        self.open = self
+            .tiles
+            .iter()
+            .position(|((lat, lon), _)| Coord::new(*lat, *lon).get_filename() == file_name);
+        self.open.map(|_| ()).ok_or(Error::FileNotFound)
+    }
+
+    fn check_hgt_file(&self, expt_len: u64) -> Result<(), Error> {
+        if self.data()?.len() as u64 != expt_len {
+            return Err(Error::Filesize);
+        }
+        Ok(())
+    }
+
+    fn read_hgt_data(&mut self, pos: u64, buffer: &mut [u8; 2]) -> Result<(), Error> {
+        self.read_hgt_block(pos, buffer)
+    }
+
+    /// Copies the samples straight from memory.
+    fn read_hgt_block(&mut self, pos: u64, buffer: &mut [u8]) -> Result<(), Error> {
+        let data = self.data()?;
+        let start = usize::try_from(pos).map_err(|_| Error::IndexOutOfBounds)?;
+        let end = start
+            .checked_add(buffer.len())
+            .ok_or(Error::IndexOutOfBounds)?;
+        let bytes = data.get(start..end).ok_or(Error::IndexOutOfBounds)?;
+        buffer.copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn close_hgt_file(&mut self) -> Result<(), Error> {
+        self.open.take().map(|_| ()).ok_or(Error::NotFound)
+    }
+}
@@ -0,0 +1,296 @@
+use heapless::Vec;
+
+use super::Coord;
+use crate::{
+    ElevationSource, Error, HgtReader,
+    resolutions::Resolution,
+    tiles::{Tile, VOID_VALUE, round_f64},
+};
+
+/// A continuous elevation surface made of several HGT files.
+///
+/// A `Mosaic` owns up to `N` readers, each wrapped in its own [`Tile`], so
+/// that up to `N` files stay open at the same time. Queries are routed to the
+/// tile covering the coordinate; when all tiles are in use, the least
+/// recently used one is switched to the requested file. The readers do not
+/// need to serve the same files: a file not found by one reader is looked
+/// up in the others, e.g. when every reader holds a single tile.
+///
+/// Adjacent HGT files duplicate their shared edge row and column. Samples on
+/// such an edge are served by any open tile containing them, so windows and
+/// interpolation near an edge do not open the neighbouring file needlessly,
+/// and a missing neighbour file does not hide the edge samples.
+///
+/// # Example
+/// ```ignore
+/// let mut mosaic = Mosaic::<MyReader, 4>::new(Resolution::SRTM3);
+/// mosaic.add_reader(MyReader::new()).ok();
+/// mosaic.add_reader(MyReader::new()).ok();
+/// let height = mosaic.get_height((49.1, 8.999))?;
+/// ```
+#[derive(Debug)]
+pub struct Mosaic<R: HgtReader, const N: usize> {
+    pub resolution: Resolution,
+    /// Tiles together with the time of their last use.
+    tiles: Vec<(Tile<R>, u32), N>,
+    clock: u32,
+}
+
+impl<R: HgtReader, const N: usize> Mosaic<R, N> {
+    /// Creates an empty `Mosaic`; readers are added with [`Mosaic::add_reader`].
+    pub fn new(res: Resolution) -> Self {
+        Mosaic {
+            resolution: res,
+            tiles: Vec::new(),
+            clock: 0,
+        }
+    }
+
+    /// Adds a reader to the mosaic.
+    /// Returns the reader back as `Err` if the mosaic already holds `N` readers.
+    pub fn add_reader(&mut self, reader: R) -> Result<(), R> {
+        if self.tiles.is_full() {
+            return Err(reader);
+        }
+        let tile = Tile::<R>::new(self.resolution, reader);
+        // cannot fail, the capacity has been checked above
+        self.tiles.push((tile, 0)).ok();
+        Ok(())
+    }
+
+    /// Returns the number of readers in the mosaic.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns `true` if no reader has been added yet.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Retrieves the height of the sample nearest to the coordinate.
+    ///
+    /// Works like [`Tile::get_height`], but keeps the files of the last `N`
+    /// tiles open and falls back to a neighbouring file for samples on a
+    /// shared edge.
+    pub fn get_height(&mut self, coord: impl Into<Coord>) -> Result<i16, Error> {
+        let coord: Coord = coord.into();
+        let n = self.intervals();
+        let gy = round_f64(coord.lat * n as f64) as i64;
+        let gx = round_f64(coord.lon * n as f64) as i64;
+
+        // every tile containing the sample, the regular one first
+        let (lat, lon) = (tile_index(gy, n), tile_index(gx, n));
+        let mut candidates = Vec::<(i64, i64), 4>::new();
+        for lat in [lat, lat - 1] {
+            for lon in [lon, lon - 1] {
+                if lat * n <= gy && gy <= (lat + 1) * n && lon * n <= gx && gx <= (lon + 1) * n {
+                    candidates.push((lat, lon)).ok();
+                }
+            }
+        }
+
+        let mut result = Err(Error::NotFound);
+        // prefer a tile that is open already
+        if let Some(&origin) = candidates
+            .iter()
+            .find(|origin| self.find_open(**origin).is_some())
+        {
+            result = self.sample(origin, gy, gx);
+        }
+        for &origin in candidates.iter() {
+            if result.is_ok() {
+                break;
+            }
+            result = self.sample(origin, gy, gx);
+        }
+        let height = result?;
+        if height == VOID_VALUE {
+            Err(Error::InvalidData)
+        } else {
+            Ok(height)
+        }
+    }
+
+    /// Reads a rectangular window of heights which may span several tiles.
+    ///
+    /// Works like [`Tile::read_window`]: the window starts at the sample
+    /// nearest to `coord` and extends `rows` samples south and `cols` samples
+    /// east. Shared edge rows and columns are read from the tile that is
+    /// already being used for the neighbouring samples.
+    pub fn read_window(
+        &mut self,
+        coord: impl Into<Coord>,
+        rows: usize,
+        cols: usize,
+        buffer: &mut [i16],
+    ) -> Result<(), Error> {
+        let coord: Coord = coord.into();
+        if rows.checked_mul(cols).is_none_or(|len| buffer.len() < len) {
+            return Err(Error::BufferTooSmall);
+        }
+        let n = self.intervals();
+        let top = round_f64(coord.lat * n as f64) as i64;
+        let left = round_f64(coord.lon * n as f64) as i64;
+
+        let mut lat: Option<i64> = None;
+        for r in 0..rows {
+            let gy = top - r as i64;
+            // stay in the current tile for its southern edge row
+            let preferred = match lat {
+                Some(lat) if gy == lat * n => lat,
+                _ => tile_index(gy - 1, n),
+            };
+            let out_row = &mut buffer[r * cols..(r + 1) * cols];
+            let mut done = 0;
+            while done < cols {
+                let (tile_lat, len) =
+                    self.read_segment(gy, left + done as i64, preferred, &mut out_row[done..])?;
+                if done == 0 {
+                    lat = Some(tile_lat);
+                }
+                done += len;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the samples of row `gy` starting at column `gx` (global indices)
+    /// up to the eastern edge of their tile into `out`, from the tile in row
+    /// `preferred` or, for samples on a shared edge, from a neighbour if that
+    /// fails. Returns the tile row used and the number of samples read.
+    fn read_segment(
+        &mut self,
+        gy: i64,
+        gx: i64,
+        preferred: i64,
+        out: &mut [i16],
+    ) -> Result<(i64, usize), Error> {
+        let n = self.intervals();
+        let other_lat = if gy == preferred * n {
+            preferred - 1
+        } else {
+            preferred + 1
+        };
+        let lats = [preferred, other_lat];
+        let lon = tile_index(gx, n);
+        let lons = [lon, lon - 1];
+        let mut result = Err(Error::NotFound);
+        for lat in lats {
+            for lon in lons {
+                if !(lat * n <= gy && gy <= (lat + 1) * n && lon * n <= gx && gx <= (lon + 1) * n) {
+                    continue;
+                }
+                let row = ((lat + 1) * n - gy) as usize;
+                let col = (gx - lon * n) as usize;
+                // the segment includes the eastern edge column of the tile
+                let len = (n as usize + 1 - col).min(out.len());
+                result = self
+                    .open((lat, lon))
+                    .and_then(|slot| self.tiles[slot].0.read_row(row, col, &mut out[..len]))
+                    .map(|_| (lat, len));
+                if result.is_ok() {
+                    return result;
+                }
+            }
+        }
+        result
+    }
+
+    /// Number of sample intervals per degree.
+    fn intervals(&self) -> i64 {
+        self.resolution.point_per_degree() as i64 - 1
+    }
+
+    /// Reads the sample with global indices `gy`, `gx` from the tile at `origin`.
+    fn sample(&mut self, origin: (i64, i64), gy: i64, gx: i64) -> Result<i16, Error> {
+        let n = self.intervals();
+        let slot = self.open(origin)?;
+        let row = ((origin.0 + 1) * n - gy) as usize;
+        let col = (gx - origin.1 * n) as usize;
+        let mut height = [0];
+        self.tiles[slot].0.read_row(row, col, &mut height)?;
+        Ok(height[0])
+    }
+
+    /// Returns the slot holding the open tile at `origin`, if any.
+    fn find_open(&self, origin: (i64, i64)) -> Option<usize> {
+        self.tiles.iter().position(|(tile, _)| {
+            tile.origin().map(|(lat, lon)| (lat as i64, lon as i64)) == Some(origin)
+        })
+    }
+
+    /// Returns the slot holding the tile at `origin`,
+    /// opening it in the least recently used slot if necessary.
+    ///
+    /// Readers may serve different sets of files: if the file is not found
+    /// by one reader, the next slot is tried, and every slot whose open
+    /// failed gets its previous file back.
+    fn open(&mut self, origin: (i64, i64)) -> Result<usize, Error> {
+        if !(-90..90).contains(&origin.0) || !(-180..180).contains(&origin.1) {
+            return Err(Error::IndexOutOfBounds);
+        }
+        self.clock = self.clock.wrapping_add(1);
+        let slot = match self.find_open(origin) {
+            Some(slot) => slot,
+            None => {
+                // unused slots first, then the least recently used
+                let mut slots = Vec::<usize, N>::new();
+                for slot in 0..self.tiles.len() {
+                    slots.push(slot).ok();
+                }
+                slots.sort_unstable_by_key(|&slot| {
+                    let (tile, used) = &self.tiles[slot];
+                    (tile.origin().is_some(), *used)
+                });
+                let mut result = Err(Error::NotFound);
+                for slot in slots {
+                    let tile = &mut self.tiles[slot].0;
+                    let previous = tile.origin();
+                    result = tile
+                        .open_tile((origin.0 as i8, origin.1 as i16))
+                        .map(|_| slot);
+                    if result.is_ok() {
+                        break;
+                    }
+                    if let Some(previous) = previous {
+                        // the file was open before, a failure here leaves the slot unused
+                        tile.open_tile(previous).ok();
+                    }
+                    if result != Err(Error::FileNotFound) {
+                        break;
+                    }
+                }
+                result?
+            }
+        };
+        self.tiles[slot].1 = self.clock;
+        Ok(slot)
+    }
+}
+
+impl<R: HgtReader, const N: usize> ElevationSource for Mosaic<R, N> {
+    fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    fn height_at(&mut self, coord: Coord) -> Result<i16, Error> {
+        self.get_height(coord)
+    }
+
+    fn read_window(
+        &mut self,
+        coord: Coord,
+        rows: usize,
+        cols: usize,
+        buffer: &mut [i16],
+    ) -> Result<(), Error> {
+        Mosaic::read_window(self, coord, rows, cols, buffer)
+    }
+}
+
+/// Returns the index of the tile containing the global sample index `g`,
+/// samples on an edge belong to the tile starting there.
+fn tile_index(g: i64, n: i64) -> i64 {
+    g.div_euclid(n)
+}
@@ -0,0 +1,282 @@
+use super::Coord;
+use crate::{
+    Error,
+    bitmap::{bitmap_len, get_bit, set_bit},
+    grid::{Grid, Sample},
+    heap::CellHeap,
+};
+
+/// The highest point on the way from a peak to higher terrain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyCol {
+    pub coord: Coord,
+    /// Height of the col in meters.
+    pub height: f64,
+    /// Height of the peak above the col in meters.
+    pub prominence: f64,
+}
+
+/// A summit found by [`find_peaks`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Peak {
+    pub coord: Coord,
+    pub row: usize,
+    pub col: usize,
+    /// Height in meters.
+    pub height: f64,
+    /// Key col, `None` if it could not be found inside the grid.
+    pub key_col: Option<KeyCol>,
+    /// Distance to the nearest higher terrain in meters,
+    /// `None` if there is none in the grid.
+    pub isolation: Option<f64>,
+}
+
+impl Peak {
+    /// Returns the prominence in meters, if the key col is known.
+    pub fn prominence(&self) -> Option<f64> {
+        self.key_col.map(|key_col| key_col.prominence)
+    }
+}
+
+/// Returns `true` if the cell is a local maximum: not lower than any of its
+/// eight neighbours and, to report a plateau only once, higher than the
+/// neighbours before it in row order. Border cells and cells next to void
+/// samples are never local maxima.
+///
+/// Only the neighbours are looked at, so a plateau that is not convex may
+/// have several such cells, or be bordered by higher terrain further away;
+/// [`find_peaks`] checks whole plateaus.
+pub fn is_local_maximum<T: Sample>(grid: &Grid<T>, row: usize, col: usize) -> bool {
+    let Some(z) = grid.neighbourhood(row, col) else {
+        return false;
+    };
+    z.iter()
+        .enumerate()
+        .all(|(i, value)| i == 4 || z[4] > *value || z[4] == *value && i > 4)
+}
+
+/// Finds the key col of a peak and with it its prominence.
+///
+/// The terrain around the peak is flooded from the top down, always with the
+/// highest cell next to the flooded area, until a cell higher than the peak
+/// is reached. The lowest cell flooded until then is the key col. Void
+/// samples are never crossed.
+///
+/// Returns `None` if the flood reaches the border of the grid first, as the
+/// key col may then lie outside; read a larger grid, if necessary from a
+/// [`crate::Mosaic`] to cross tile boundaries. `keys` and `heap` are scratch
+/// space of `rows * cols` cells each.
+///
+/// # Errors
+///
+/// * `Error::IndexOutOfBounds` - the cell lies outside the grid.
+/// * `Error::BufferTooSmall` - `keys` or `heap` is too small.
+/// * `Error::InvalidData` - the cell is void.
+pub fn key_col<T: Sample>(
+    grid: &Grid<T>,
+    row: usize,
+    col: usize,
+    keys: &mut [f32],
+    heap: &mut [u32],
+) -> Result<Option<KeyCol>, Error> {
+    let (rows, cols, len) = (grid.rows, grid.cols, grid.len());
+    if row >= rows || col >= cols {
+        return Err(Error::IndexOutOfBounds);
+    }
+    if keys.len() < len || heap.len() < len || len > u32::MAX as usize {
+        return Err(Error::BufferTooSmall);
+    }
+    let peak = grid.get(row, col).ok_or(Error::InvalidData)?;
+    // the heap pops the lowest key first, so heights are stored negated
+    let keys = &mut keys[..len];
+    keys.fill(f32::NAN);
+    let mut heap = CellHeap::new(heap);
+    let start = row * cols + col;
+    keys[start] = -peak as f32;
+    heap.push(start, keys);
+
+    let mut lowest = (peak, start);
+    while let Some(index) = heap.pop(keys) {
+        let (r, c) = (index / cols, index % cols);
+        let height = grid.get(r, c).unwrap_or(peak);
+        if height > peak {
+            return Ok(Some(KeyCol {
+                coord: grid.coord(lowest.1 / cols, lowest.1 % cols),
+                height: lowest.0,
+                prominence: peak - lowest.0,
+            }));
+        }
+        if r == 0 || c == 0 || r + 1 == rows || c + 1 == cols {
+            return Ok(None);
+        }
+        // the last cell at the lowest level is the one the flood left it by
+        if height <= lowest.0 {
+            lowest = (height, index);
+        }
+        for neighbour in [
+            index - cols - 1,
+            index - cols,
+            index - cols + 1,
+            index - 1,
+            index + 1,
+            index + cols - 1,
+            index + cols,
+            index + cols + 1,
+        ] {
+            if !keys[neighbour].is_nan() {
+                continue;
+            }
+            if let Some(height) = grid.heights[neighbour].value() {
+                keys[neighbour] = -height as f32;
+                heap.push(neighbour, keys);
+            }
+        }
+    }
+    // enclosed by voids
+    Ok(None)
+}
+
+/// Returns the distance in meters from a cell to the nearest cell that is
+/// higher, `None` if the cell is void or the highest of the grid.
+pub fn isolation<T: Sample>(grid: &Grid<T>, row: usize, col: usize) -> Option<f64> {
+    let height = grid.get(row, col)?;
+    let origin = grid.coord(row, col);
+    let (dx, dy) = grid.cell_size(row);
+    let mut nearest: Option<f64> = None;
+    // search rings of growing size until no closer cell can follow
+    for ring in 1..grid.rows.max(grid.cols) {
+        if nearest.is_some_and(|nearest| nearest < ring as f64 * dx.min(dy) * 0.99) {
+            break;
+        }
+        let (top, bottom) = (row as isize - ring as isize, row as isize + ring as isize);
+        let (left, right) = (col as isize - ring as isize, col as isize + ring as isize);
+        for r in top..=bottom {
+            let step = if r == top || r == bottom {
+                1
+            } else {
+                right - left
+            };
+            for c in (left..=right).step_by(step as usize) {
+                if grid.get_signed(r, c).is_some_and(|value| value > height) {
+                    let distance = origin.distance_to(grid.coord(r as usize, c as usize));
+                    nearest = Some(nearest.map_or(distance, |nearest| nearest.min(distance)));
+                }
+            }
+        }
+    }
+    nearest
+}
+
+/// Finds the summits of a grid.
+///
+/// Every local maximum (see [`is_local_maximum`]) is checked for its key col
+/// and kept if its prominence is at least `min_prominence` meters or cannot
+/// be determined inside the grid. A plateau is flooded as a whole, reported
+/// once at its first cell in row order and only if no higher terrain, void
+/// sample or grid border touches it. `peaks` receives the highest of these
+/// summits in descending order of height, together with their isolation;
+/// the number of peaks written is returned. `keys` and `heap` are scratch
+/// space as described for [`key_col`], `visited` is a bitmap of
+/// [`crate::bitmap::bitmap_len`] bytes marking the flooded plateaus.
+///
+/// # Errors
+///
+/// * `Error::BufferTooSmall` - `keys`, `heap` or `visited` is too small.
+pub fn find_peaks<T: Sample>(
+    grid: &Grid<T>,
+    min_prominence: f64,
+    peaks: &mut [Peak],
+    keys: &mut [f32],
+    heap: &mut [u32],
+    visited: &mut [u8],
+) -> Result<usize, Error> {
+    let len = grid.len();
+    let bitmap = bitmap_len(grid.rows, grid.cols);
+    if keys.len() < len || heap.len() < len || visited.len() < bitmap || len > u32::MAX as usize {
+        return Err(Error::BufferTooSmall);
+    }
+    visited[..bitmap].fill(0);
+    let mut count = 0;
+    for row in 0..grid.rows {
+        for col in 0..grid.cols {
+            if get_bit(visited, row * grid.cols + col) || !is_local_maximum(grid, row, col) {
+                continue;
+            }
+            let height = grid.get(row, col).unwrap_or_default();
+            if count == peaks.len() && peaks.last().is_none_or(|last| last.height >= height) {
+                continue;
+            }
+            if !is_summit_plateau(grid, row, col, visited, heap) {
+                continue;
+            }
+            let key_col = key_col(grid, row, col, keys, heap)?;
+            if key_col.is_some_and(|key_col| key_col.prominence < min_prominence) {
+                continue;
+            }
+            // insert sorted by height, dropping the lowest peak if full
+            let position = peaks[..count]
+                .iter()
+                .position(|peak| peak.height < height)
+                .unwrap_or(count);
+            count = (count + 1).min(peaks.len());
+            peaks[position..count].rotate_right(1);
+            peaks[position] = Peak {
+                coord: grid.coord(row, col),
+                row,
+                col,
+                height,
+                key_col,
+                isolation: None,
+            };
+        }
+    }
+    for peak in &mut peaks[..count] {
+        peak.isolation = isolation(grid, peak.row, peak.col);
+    }
+    Ok(count)
+}
+
+/// Floods the plateau of equal height around a cell, marking it in `visited`
+/// with `stack` as scratch space, and returns `true` if no higher terrain,
+/// void sample or grid border touches it.
+fn is_summit_plateau<T: Sample>(
+    grid: &Grid<T>,
+    row: usize,
+    col: usize,
+    visited: &mut [u8],
+    stack: &mut [u32],
+) -> bool {
+    let height = grid.get(row, col);
+    let start = row * grid.cols + col;
+    set_bit(visited, start);
+    stack[0] = start as u32;
+    let (mut len, mut summit) = (1, true);
+    while len > 0 {
+        len -= 1;
+        let index = stack[len] as usize;
+        let (r, c) = ((index / grid.cols) as isize, (index % grid.cols) as isize);
+        for (dr, dc) in [
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ] {
+            let value = grid.get_signed(r + dr, c + dc);
+            if value.is_none_or(|value| Some(value) > height) {
+                summit = false;
+            } else if value == height {
+                let neighbour = (r + dr) as usize * grid.cols + (c + dc) as usize;
+                if !get_bit(visited, neighbour) {
+                    set_bit(visited, neighbour);
+                    stack[len] = neighbour as u32;
+                    len += 1;
+                }
+            }
+        }
+    }
+    summit
+}
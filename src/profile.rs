@@ -0,0 +1,179 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use super::Coord;
+use crate::{ElevationSource, Error};
+
+/// One sample of an elevation profile.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct ProfileSample {
+    /// Distance from the start of the path in meters.
+    pub distance: f64,
+    /// Position of the sample.
+    pub coord: Coord,
+    /// Terrain height in meters, `None` for void samples.
+    pub height: Option<i16>,
+}
+
+/// Summary of an elevation profile.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct ProfileSummary {
+    /// Length of the path in meters.
+    pub length: f64,
+    /// Lowest height along the path, `None` if all samples are void.
+    pub min: Option<i16>,
+    /// Highest height along the path, `None` if all samples are void.
+    pub max: Option<i16>,
+    /// Sum of all height gains between consecutive valid samples in meters.
+    pub total_ascent: u32,
+    /// Sum of all height losses between consecutive valid samples in meters.
+    pub total_descent: u32,
+}
+
+impl ProfileSummary {
+    /// Calculates the summary of the given profile samples.
+    pub fn from_samples(samples: &[ProfileSample]) -> Self {
+        let mut summary = ProfileSummary {
+            length: samples.last().map_or(0.0, |sample| sample.distance),
+            ..Default::default()
+        };
+        let mut last: Option<i16> = None;
+        for height in samples.iter().filter_map(|sample| sample.height) {
+            summary.min = Some(summary.min.map_or(height, |min| min.min(height)));
+            summary.max = Some(summary.max.map_or(height, |max| max.max(height)));
+            if let Some(last) = last {
+                let diff = height as i32 - last as i32;
+                if diff > 0 {
+                    summary.total_ascent += diff as u32;
+                } else {
+                    summary.total_descent += (-diff) as u32;
+                }
+            }
+            last = Some(height);
+        }
+        summary
+    }
+}
+
+/// Returns the number of samples [`profile`] produces for the path,
+/// so a buffer of the right size can be prepared.
+pub fn sample_count(path: &[Coord], spacing: f64) -> usize {
+    if path.is_empty() || spacing <= 0.0 {
+        return 0;
+    }
+    let length: f64 = path.windows(2).map(|leg| leg[0].distance_to(leg[1])).sum();
+    // one sample every `spacing` meters plus the end of the path
+    let steps = (length / spacing) as usize;
+    if steps as f64 * spacing < length {
+        steps + 2
+    } else {
+        steps + 1
+    }
+}
+
+/// Samples the terrain along a polyline.
+///
+/// Samples are taken every `spacing` meters along the path, measured over
+/// all legs, and at the end of the path. Each height is the nearest sample
+/// of the source, void samples are returned with `height: None`. The samples
+/// are written into `buffer`; use [`sample_count`] to size it.
+///
+/// Returns the number of samples written and the profile summary.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the path is empty or `spacing` is not positive.
+/// * `Error::BufferTooSmall` - `buffer` cannot hold all samples.
+/// * any error of the source except `Error::InvalidData`.
+///
+/// # Example
+/// ```ignore
+/// let path = [Coord::new(49.1, 8.2), Coord::new(49.2, 8.3)];
+/// let mut samples = [ProfileSample::default(); 512];
+/// let (count, summary) = profile(&mut tile, &path, 30.0, &mut samples)?;
+/// ```
+pub fn profile<S: ElevationSource>(
+    source: &mut S,
+    path: &[Coord],
+    spacing: f64,
+    buffer: &mut [ProfileSample],
+) -> Result<(usize, ProfileSummary), Error> {
+    let count = sample_count(path, spacing);
+    if count == 0 {
+        return Err(Error::InvalidParameter);
+    }
+    if buffer.len() < count {
+        return Err(Error::BufferTooSmall);
+    }
+
+    let mut index = 0;
+    // distance from the start of the path to the start of the current leg
+    let mut leg_start = 0.0;
+    for leg in path.windows(2) {
+        let leg_length = leg[0].distance_to(leg[1]);
+        while index < count - 1 {
+            let distance = index as f64 * spacing;
+            if distance > leg_start + leg_length {
+                break;
+            }
+            let fraction = if leg_length > 0.0 {
+                (distance - leg_start) / leg_length
+            } else {
+                0.0
+            };
+            buffer[index] = sample(source, leg[0].interpolate(leg[1], fraction), distance)?;
+            index += 1;
+        }
+        leg_start += leg_length;
+    }
+    // the end of the path, also the only sample of a single point path
+    let end = path[path.len() - 1];
+    buffer[index] = sample(source, end, leg_start)?;
+    index += 1;
+
+    Ok((index, ProfileSummary::from_samples(&buffer[..index])))
+}
+
+/// Samples the terrain on the great circle between two points,
+/// see [`profile`].
+pub fn profile_between<S: ElevationSource>(
+    source: &mut S,
+    start: impl Into<Coord>,
+    end: impl Into<Coord>,
+    spacing: f64,
+    buffer: &mut [ProfileSample],
+) -> Result<(usize, ProfileSummary), Error> {
+    profile(source, &[start.into(), end.into()], spacing, buffer)
+}
+
+/// Samples the terrain along a polyline into a newly allocated `Vec`,
+/// see [`profile`].
+#[cfg(feature = "alloc")]
+pub fn profile_vec<S: ElevationSource>(
+    source: &mut S,
+    path: &[Coord],
+    spacing: f64,
+) -> Result<(Vec<ProfileSample>, ProfileSummary), Error> {
+    let mut samples = alloc::vec![ProfileSample::default(); sample_count(path, spacing)];
+    let (count, summary) = profile(source, path, spacing, &mut samples)?;
+    samples.truncate(count);
+    Ok((samples, summary))
+}
+
+/// Reads one profile sample, void samples are kept with `height: None`.
+fn sample<S: ElevationSource>(
+    source: &mut S,
+    coord: Coord,
+    distance: f64,
+) -> Result<ProfileSample, Error> {
+    let height = match source.height_at(coord) {
+        Ok(height) => Some(height),
+        Err(Error::InvalidData) => None,
+        Err(err) => return Err(err),
+    };
+    Ok(ProfileSample {
+        distance,
+        coord,
+        height,
+    })
+}
@@ -0,0 +1,346 @@
+use super::Coord;
+use crate::{
+    ElevationSource, Error,
+    grid::snap,
+    los::{LosOptions, Position, STANDARD_K_FACTOR, earth_bulge},
+    viewshed::dimensions,
+};
+
+/// Speed of light in meters per second.
+pub const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+
+/// Returns the radius in meters of the first Fresnel zone at a point `d1`
+/// meters from one and `d2` meters from the other antenna, for a frequency
+/// in Hz.
+pub fn fresnel_radius(d1: f64, d2: f64, frequency: f64) -> f64 {
+    let wavelength = SPEED_OF_LIGHT / frequency;
+    libm::sqrt(wavelength * d1 * d2 / (d1 + d2))
+}
+
+/// A terrain sample on a radio path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FresnelPoint {
+    pub coord: Coord,
+    /// Distance from the start in meters.
+    pub distance: f64,
+    /// Terrain height above mean sea level, including the earth bulge
+    /// if curvature is applied.
+    pub terrain_height: f64,
+    /// Height of the direct line between the antennas above mean sea level.
+    pub line_height: f64,
+    /// Radius of the first Fresnel zone in meters.
+    pub radius: f64,
+}
+
+impl FresnelPoint {
+    /// Returns the clearance of the terrain below the direct line in percent
+    /// of the Fresnel radius: 100 or more is clear, 0 grazes the line and
+    /// negative values block the line of sight.
+    pub fn clearance(&self) -> f64 {
+        (self.line_height - self.terrain_height) / self.radius * 100.0
+    }
+}
+
+/// Result of [`fresnel_clearance`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FresnelReport {
+    /// Length of the path in meters.
+    pub length: f64,
+    /// The sample with the least clearance,
+    /// `None` if the path is too short for any or all are void.
+    pub worst: Option<FresnelPoint>,
+}
+
+impl FresnelReport {
+    /// Returns the smallest clearance along the path in percent of the
+    /// Fresnel radius, see [`FresnelPoint::clearance`].
+    pub fn clearance(&self) -> Option<f64> {
+        self.worst.map(|worst| worst.clearance())
+    }
+
+    /// Returns `true` if the terrain stays out of the given percentage of the
+    /// first Fresnel zone, usually 60 %.
+    pub fn is_clear(&self, percent: f64) -> bool {
+        self.clearance()
+            .is_none_or(|clearance| clearance >= percent)
+    }
+
+    /// Returns `true` if the terrain does not block the direct line.
+    pub fn line_of_sight(&self) -> bool {
+        self.is_clear(0.0)
+    }
+}
+
+/// Analyses the first Fresnel zone of a radio link between two antennas.
+///
+/// The terrain is sampled along the great circle between the antennas like
+/// in [`crate::los::line_of_sight`], raised by the earth bulge for the
+/// k-factor of the options, and compared with the Fresnel radius for
+/// `frequency` in Hz at every sample. Void samples are skipped.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the frequency or spacing is not positive.
+/// * `Error::InvalidData` - the terrain height of an `AboveGround` antenna is void.
+/// * any error of the source except `Error::InvalidData`.
+pub fn fresnel_clearance<S: ElevationSource>(
+    source: &mut S,
+    from: Position,
+    to: Position,
+    frequency: f64,
+    options: &LosOptions,
+) -> Result<FresnelReport, Error> {
+    let spacing = options
+        .spacing
+        .unwrap_or_else(|| source.resolution().cell_size(from.coord.lat).1);
+    if spacing <= 0.0 || frequency <= 0.0 || frequency.is_nan() {
+        return Err(Error::InvalidParameter);
+    }
+    let (start, end) = (from.height(source)?, to.height(source)?);
+    let length = from.coord.distance_to(to.coord);
+    let mut report = FresnelReport {
+        length,
+        worst: None,
+    };
+
+    let steps = (length / spacing) as usize;
+    for i in 1..=steps {
+        let distance = i as f64 * spacing;
+        if distance >= length {
+            break;
+        }
+        let coord = from.coord.interpolate(to.coord, distance / length);
+        let mut terrain = match source.interpolated_height(coord) {
+            Ok(height) => height,
+            Err(Error::InvalidData) => continue,
+            Err(err) => return Err(err),
+        };
+        if options.earth_curvature {
+            terrain += earth_bulge(distance, length - distance, options.k_factor);
+        }
+        let point = FresnelPoint {
+            coord,
+            distance,
+            terrain_height: terrain,
+            line_height: start + (end - start) * distance / length,
+            radius: fresnel_radius(distance, length - distance, frequency),
+        };
+        if report
+            .clearance()
+            .is_none_or(|worst| point.clearance() < worst)
+        {
+            report.worst = Some(point);
+        }
+    }
+    Ok(report)
+}
+
+/// Parameters of [`path_loss`] and [`coverage`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PropagationOptions {
+    /// Frequency in Hz.
+    pub frequency: f64,
+    /// Take the curvature of the earth into account.
+    pub earth_curvature: bool,
+    /// Refraction coefficient, only used together with `earth_curvature`.
+    pub k_factor: f64,
+}
+
+impl Default for PropagationOptions {
+    /// 868 MHz with earth curvature and standard refraction.
+    fn default() -> Self {
+        PropagationOptions {
+            frequency: 868e6,
+            earth_curvature: true,
+            k_factor: STANDARD_K_FACTOR,
+        }
+    }
+}
+
+/// Placement of a path loss raster written by [`coverage`], laid out like a
+/// [`crate::viewshed::Viewshed`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Coverage {
+    /// Position of the cell in row 0, column 0.
+    pub origin: Coord,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+/// Returns the free space path loss in dB over a distance in meters.
+pub fn free_space_loss(distance: f64, frequency: f64) -> f64 {
+    20.0 * libm::log10(4.0 * core::f64::consts::PI * distance * frequency / SPEED_OF_LIGHT)
+}
+
+/// Returns the loss in dB of a single knife edge with the Fresnel-Kirchhoff
+/// diffraction parameter `v`, approximated as in ITU-R P.526.
+pub fn knife_edge_loss(v: f64) -> f64 {
+    if v <= -0.78 {
+        return 0.0;
+    }
+    6.9 + 20.0 * libm::log10(libm::sqrt((v - 0.1) * (v - 0.1) + 1.0) + v - 0.1)
+}
+
+/// Computes the path loss in dB between two antennas.
+///
+/// The loss is the free space loss plus the diffraction loss of the terrain
+/// after Deygout: the edge obstructing the path most is taken as a knife
+/// edge, then the strongest edges between it and either antenna. The terrain
+/// profile is read with [`ElevationSource::height_at`] into `profile`, at the
+/// cell size of the source but with at most `profile.len()` samples; void
+/// samples never obstruct.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the frequency is not positive or the antennas are at the same place.
+/// * `Error::BufferTooSmall` - `profile` holds less than 3 samples.
+/// * `Error::InvalidData` - the terrain height of an `AboveGround` antenna is void.
+/// * any error of the source except `Error::InvalidData`.
+pub fn path_loss<S: ElevationSource>(
+    source: &mut S,
+    from: Position,
+    to: Position,
+    options: &PropagationOptions,
+    profile: &mut [f64],
+) -> Result<f64, Error> {
+    let (start, end) = (from.height(source)?, to.height(source)?);
+    profile_loss(
+        source,
+        (from.coord, start),
+        (to.coord, end),
+        options,
+        profile,
+    )
+}
+
+/// Computes a path loss map around a transmitter.
+///
+/// Every cell within `radius` meters gets the loss of [`path_loss`] to a
+/// receiver `receiver_height` meters above its terrain, in dB. The cells
+/// cover the [`crate::viewshed::dimensions`] around the transmitter, row by
+/// row from the north-west; `out` must hold `rows * cols` values. Cells
+/// outside the radius, void cells and the transmitter cell are set to NaN.
+/// Each cell reads a profile of up to `profile.len()` heights, so wrapping
+/// the reader in a [`crate::CachedReader`] is recommended.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the radius or frequency is not positive.
+/// * `Error::BufferTooSmall` - `out` is too small or `profile` holds less than 3 samples.
+/// * `Error::InvalidData` - the terrain height of an `AboveGround` transmitter is void.
+/// * any error of the source except `Error::InvalidData`.
+pub fn coverage<S: ElevationSource>(
+    source: &mut S,
+    transmitter: Position,
+    receiver_height: f64,
+    radius: f64,
+    options: &PropagationOptions,
+    out: &mut [f32],
+    profile: &mut [f64],
+) -> Result<Coverage, Error> {
+    if radius <= 0.0 || radius.is_nan() || options.frequency <= 0.0 || options.frequency.is_nan() {
+        return Err(Error::InvalidParameter);
+    }
+    let resolution = source.resolution();
+    let center = snap(transmitter.coord, resolution);
+    let (rows, cols) = dimensions(resolution, center.lat, radius);
+    if rows.checked_mul(cols).is_none_or(|len| out.len() < len) || profile.len() < 3 {
+        return Err(Error::BufferTooSmall);
+    }
+    let step = resolution.step();
+    let origin = Coord {
+        lat: center.lat + (rows / 2) as f64 * step,
+        lon: center.lon - (cols / 2) as f64 * step,
+    };
+    let antenna = (transmitter.coord, transmitter.height(source)?);
+
+    for row in 0..rows {
+        for col in 0..cols {
+            let coord = Coord {
+                lat: origin.lat - row as f64 * step,
+                lon: origin.lon + col as f64 * step,
+            };
+            let distance = transmitter.coord.distance_to(coord);
+            out[row * cols + col] = if distance > radius || distance < 1.0 {
+                f32::NAN
+            } else {
+                match source.height_at(coord) {
+                    Ok(height) => {
+                        let receiver = (coord, height as f64 + receiver_height);
+                        profile_loss(source, antenna, receiver, options, profile)? as f32
+                    }
+                    Err(Error::InvalidData) => f32::NAN,
+                    Err(err) => return Err(err),
+                }
+            };
+        }
+    }
+    Ok(Coverage { origin, rows, cols })
+}
+
+/// Path loss between two antennas given by coordinate and height above mean sea level.
+fn profile_loss<S: ElevationSource>(
+    source: &mut S,
+    (from, start): (Coord, f64),
+    (to, end): (Coord, f64),
+    options: &PropagationOptions,
+    profile: &mut [f64],
+) -> Result<f64, Error> {
+    let length = from.distance_to(to);
+    if options.frequency <= 0.0 || options.frequency.is_nan() || length == 0.0 {
+        return Err(Error::InvalidParameter);
+    }
+    if profile.len() < 3 {
+        return Err(Error::BufferTooSmall);
+    }
+    let cell = source.resolution().cell_size(from.lat).1;
+    let intervals = ((length / cell) as usize).clamp(2, profile.len() - 1);
+    let spacing = length / intervals as f64;
+    for (i, height) in profile.iter_mut().enumerate().take(intervals).skip(1) {
+        let distance = i as f64 * spacing;
+        let coord = from.interpolate(to, distance / length);
+        *height = match source.height_at(coord) {
+            Ok(height) => height as f64,
+            Err(Error::InvalidData) => f64::NEG_INFINITY,
+            Err(err) => return Err(err),
+        };
+        if options.earth_curvature {
+            *height += earth_bulge(distance, length - distance, options.k_factor);
+        }
+    }
+    let wavelength = SPEED_OF_LIGHT / options.frequency;
+    let path = &profile[..=intervals];
+    let diffraction = deygout(path, spacing, (0, start), (intervals, end), wavelength, 1);
+    Ok(free_space_loss(length, options.frequency) + diffraction)
+}
+
+/// Diffraction loss in dB of the terrain between two points of a profile,
+/// recursing `depth` times into the parts on both sides of the main edge.
+fn deygout(
+    profile: &[f64],
+    spacing: f64,
+    (a, height_a): (usize, f64),
+    (b, height_b): (usize, f64),
+    wavelength: f64,
+    depth: usize,
+) -> f64 {
+    let mut main: Option<(usize, f64)> = None;
+    for (i, height) in profile.iter().enumerate().take(b).skip(a + 1) {
+        let (d1, d2) = ((i - a) as f64 * spacing, (b - i) as f64 * spacing);
+        let line = height_a + (height_b - height_a) * d1 / (d1 + d2);
+        let v = (height - line) * libm::sqrt(2.0 * (d1 + d2) / (wavelength * d1 * d2));
+        if main.is_none_or(|(_, max)| v > max) {
+            main = Some((i, v));
+        }
+    }
+    let Some((edge, v)) = main.filter(|(_, v)| *v > -0.78) else {
+        return 0.0;
+    };
+    let mut loss = knife_edge_loss(v);
+    if depth > 0 {
+        let top = (edge, profile[edge]);
+        loss += deygout(profile, spacing, (a, height_a), top, wavelength, depth - 1)
+            + deygout(profile, spacing, top, (b, height_b), wavelength, depth - 1);
+    }
+    loss
+}
@@ -0,0 +1,220 @@
+use super::Coord;
+use crate::{
+    Bounds, ElevationSource, Error,
+    coords::{floor_f64, whole_f64},
+    grid::{Grid, Sample, snap},
+    tiles::{VOID_VALUE, round_f64},
+};
+
+/// How [`resample`] and [`resample_grid`] derive a target sample from the
+/// source samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResampleMethod {
+    /// The nearest source sample.
+    Nearest,
+    /// Bilinear interpolation of the four surrounding source samples,
+    /// leaving out void samples like [`ElevationSource::interpolated_height`].
+    Bilinear,
+    /// Mean of the valid source samples within the target cell, for
+    /// downsampling. Falls back to the nearest sample when upsampling.
+    Average,
+}
+
+/// Resamples the terrain of a bounding box to a grid of `rows` x `cols`
+/// samples, e.g. to produce SRTM3 tiles from SRTM1 data or coarse overviews.
+///
+/// The target samples are spread evenly with the first and last rows and
+/// columns on the edges of the box, like the samples of an HGT file: an SRTM3
+/// tile is the box of the whole degree with 1201 x 1201 samples. They are
+/// written row by row from the north-west into `out` and rounded to whole
+/// meters; samples without valid source data are set to [`VOID_VALUE`]. The
+/// cells averaged by [`ResampleMethod::Average`] are clipped to the box.
+/// Source samples on the edges of the box are read from the tile inside the
+/// box, so a single tile can be resampled as a whole.
+///
+/// Averaging reads every source sample once per target sample, wrapping the
+/// reader in a [`crate::CachedReader`] avoids most of the file accesses.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the box is empty or `rows` or `cols` is less than 2.
+/// * `Error::BufferTooSmall` - `out` holds less than `rows * cols` samples.
+/// * any error of the source except `Error::InvalidData`.
+///
+/// # Example
+/// ```ignore
+/// let bounds = Bounds::new((50.0, 8.0), (51.0, 9.0));
+/// resample(&mut srtm1_tile, bounds, 1201, 1201, ResampleMethod::Average, &mut srtm3)?;
+/// ```
+pub fn resample<S: ElevationSource>(
+    source: &mut S,
+    bounds: Bounds,
+    rows: usize,
+    cols: usize,
+    method: ResampleMethod,
+    out: &mut [i16],
+) -> Result<(), Error> {
+    if bounds.north <= bounds.south || bounds.east <= bounds.west {
+        return Err(Error::InvalidParameter);
+    }
+    let step = source.resolution().step();
+    let origin = snap(
+        Coord {
+            lat: bounds.north,
+            lon: bounds.west,
+        },
+        source.resolution(),
+    );
+    // the box in source samples from `origin`, rows counting southwards
+    let extent = (
+        (
+            (origin.lat - bounds.north) / step,
+            (origin.lat - bounds.south) / step,
+        ),
+        (
+            (bounds.west - origin.lon) / step,
+            (bounds.east - origin.lon) / step,
+        ),
+    );
+    let center = Coord {
+        lat: (bounds.north + bounds.south) / 2.0,
+        lon: (bounds.west + bounds.east) / 2.0,
+    };
+    // a quarter step towards the center selects the same sample, but in the
+    // tile inside the box for samples on the edge between two tiles
+    let inwards = |value: f64, center: f64| {
+        if value > center {
+            value - step / 4.0
+        } else {
+            value + step / 4.0
+        }
+    };
+    let fetch = |row: f64, col: f64| {
+        let coord = Coord {
+            lat: inwards(origin.lat - row * step, center.lat),
+            lon: inwards(origin.lon + col * step, center.lon),
+        };
+        match source.height_at(coord) {
+            Ok(height) => Ok(Some(height as f64)),
+            Err(Error::InvalidData) => Ok(None),
+            Err(err) => Err(err),
+        }
+    };
+    resample_with(fetch, extent, rows, cols, method, out)
+}
+
+/// Resamples a grid to `rows` x `cols` samples covering the same area,
+/// see [`resample`].
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the grid or the target has less than 2 rows or columns.
+/// * `Error::BufferTooSmall` - `out` holds less than `rows * cols` samples.
+pub fn resample_grid<T: Sample>(
+    grid: &Grid<T>,
+    rows: usize,
+    cols: usize,
+    method: ResampleMethod,
+    out: &mut [i16],
+) -> Result<(), Error> {
+    if grid.rows < 2 || grid.cols < 2 {
+        return Err(Error::InvalidParameter);
+    }
+    let extent = ((0.0, (grid.rows - 1) as f64), (0.0, (grid.cols - 1) as f64));
+    let fetch = |row: f64, col: f64| Ok(grid.get_signed(row as isize, col as isize));
+    resample_with(fetch, extent, rows, cols, method, out)
+}
+
+/// Resamples the source samples within `extent`, given as the row and column
+/// ranges in source samples; `fetch` returns the sample at whole indices.
+fn resample_with<F>(
+    mut fetch: F,
+    ((top, bottom), (left, right)): ((f64, f64), (f64, f64)),
+    rows: usize,
+    cols: usize,
+    method: ResampleMethod,
+    out: &mut [i16],
+) -> Result<(), Error>
+where
+    F: FnMut(f64, f64) -> Result<Option<f64>, Error>,
+{
+    if rows < 2 || cols < 2 {
+        return Err(Error::InvalidParameter);
+    }
+    if rows.checked_mul(cols).is_none_or(|len| out.len() < len) {
+        return Err(Error::BufferTooSmall);
+    }
+    // target spacing in source samples
+    let (dy, dx) = (
+        (bottom - top) / (rows - 1) as f64,
+        (right - left) / (cols - 1) as f64,
+    );
+    for row in 0..rows {
+        let y = whole_f64(top + row as f64 * dy);
+        for col in 0..cols {
+            let x = whole_f64(left + col as f64 * dx);
+            let value = match method {
+                ResampleMethod::Nearest => fetch(round_f64(y), round_f64(x))?,
+                ResampleMethod::Bilinear => bilinear(&mut fetch, y, x)?,
+                ResampleMethod::Average => {
+                    // cell of the target sample clipped to the extent, at
+                    // least the nearest source sample; the tolerance keeps
+                    // samples on the cell edges despite rounding errors
+                    let span = |center: f64, half: f64, low: f64, high: f64| {
+                        let first = -floor_f64(-(center - half).max(low) + 1e-9);
+                        let last = floor_f64((center + half).min(high) + 1e-9);
+                        if first <= last {
+                            (first, last)
+                        } else {
+                            (round_f64(center), round_f64(center))
+                        }
+                    };
+                    let (first_row, last_row) = span(y, dy / 2.0, top, bottom);
+                    let (first_col, last_col) = span(x, dx / 2.0, left, right);
+                    let (mut sum, mut count) = (0.0, 0usize);
+                    let mut r = first_row;
+                    while r <= last_row {
+                        let mut c = first_col;
+                        while c <= last_col {
+                            if let Some(height) = fetch(r, c)? {
+                                sum += height;
+                                count += 1;
+                            }
+                            c += 1.0;
+                        }
+                        r += 1.0;
+                    }
+                    (count > 0).then(|| sum / count as f64)
+                }
+            };
+            out[row * cols + col] = value.map_or(VOID_VALUE, |value| round_f64(value) as i16);
+        }
+    }
+    Ok(())
+}
+
+/// Bilinear interpolation at a fractional sample position, leaving out void samples.
+fn bilinear<F>(fetch: &mut F, y: f64, x: f64) -> Result<Option<f64>, Error>
+where
+    F: FnMut(f64, f64) -> Result<Option<f64>, Error>,
+{
+    let (y0, x0) = (floor_f64(y), floor_f64(x));
+    let (fy, fx) = (y - y0, x - x0);
+    let mut sum = 0.0;
+    let mut weights = 0.0;
+    for (dy, dx, weight) in [
+        (0.0, 0.0, (1.0 - fy) * (1.0 - fx)),
+        (0.0, 1.0, (1.0 - fy) * fx),
+        (1.0, 0.0, fy * (1.0 - fx)),
+        (1.0, 1.0, fy * fx),
+    ] {
+        if weight <= 0.0 {
+            continue;
+        }
+        if let Some(height) = fetch(y0 + dy, x0 + dx)? {
+            sum += height * weight;
+            weights += weight;
+        }
+    }
+    Ok((weights > 0.0).then(|| sum / weights))
+}
@@ -1,3 +1,5 @@
+use crate::coords::EARTH_RADIUS;
+
 const EXTENT: usize = 3600;
 
 #[derive(PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Debug, Default)]
@@ -19,7 +21,6 @@ impl Resolution {
     ///   * `SRTM05`: 7201 points per degree
     ///   * `SRTM1`: 3601 points per degree
     ///   * `SRTM3`: 1201 points per degree
-
     pub const fn point_per_degree(&self) -> usize {
         match self {
             Resolution::SRTM05 => EXTENT * 2 + 1,
@@ -27,6 +28,19 @@ impl Resolution {
             Resolution::SRTM3 => EXTENT / 3 + 1,
         }
     }
+    /// Returns the distance between two neighbouring samples in degrees.
+    pub fn step(&self) -> f64 {
+        1.0 / (self.point_per_degree() - 1) as f64
+    }
+
+    /// Returns the size of one cell in meters at the given latitude as
+    /// (east-west, north-south). The east-west size shrinks with the cosine
+    /// of the latitude.
+    pub fn cell_size(&self, lat: f64) -> (f64, f64) {
+        let north_south = EARTH_RADIUS * self.step().to_radians();
+        (north_south * libm::cos(lat.to_radians()), north_south)
+    }
+
     /// Calculates the expected length of an HGT file for the given
     /// resolution, which is the number of data points per degree of
     /// latitude or longitude, times the number of data points per degree
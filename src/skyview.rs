@@ -0,0 +1,139 @@
+use crate::{
+    ElevationSource, Error,
+    grid::{Grid, Sample},
+    horizon::{HorizonOptions, horizon_elevation},
+    los::Position,
+    tiles::round_f64,
+};
+
+/// Parameters of the sky view functions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkyViewOptions {
+    /// Number of azimuths scanned, evenly spread around the horizon.
+    pub directions: usize,
+    /// Distance in meters up to which the terrain is scanned.
+    pub max_distance: f64,
+}
+
+impl Default for SkyViewOptions {
+    /// 16 directions up to 1 km.
+    fn default() -> Self {
+        SkyViewOptions {
+            directions: 16,
+            max_distance: 1000.0,
+        }
+    }
+}
+
+/// Returns the part of the sky hidden by a horizon at `angle` degrees in one
+/// direction, `sin(angle)`; angles below the horizontal plane hide nothing.
+fn hidden(angle: f64) -> f64 {
+    libm::sin(angle.max(0.0).to_radians())
+}
+
+/// Computes the sky view factor at a position, the fraction of the sky
+/// hemisphere not hidden by the terrain, from 1 on open plains to near 0 at
+/// the bottom of deep gorges.
+///
+/// The horizon is scanned with [`horizon_elevation`] in `directions`
+/// azimuths, with earth curvature and standard refraction. Directions in
+/// which all samples are void count as open.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - `directions` is 0 or the distance is not positive and finite.
+/// * `Error::InvalidData` - the terrain at an `AboveGround` position is void.
+/// * any error of the source except `Error::InvalidData`.
+pub fn sky_view<S: ElevationSource>(
+    source: &mut S,
+    position: Position,
+    options: &SkyViewOptions,
+) -> Result<f64, Error> {
+    if options.directions == 0 || !options.max_distance.is_finite() {
+        return Err(Error::InvalidParameter);
+    }
+    let horizon = HorizonOptions {
+        max_distance: options.max_distance,
+        ..Default::default()
+    };
+    let mut sum = 0.0;
+    for i in 0..options.directions {
+        let azimuth = i as f64 * 360.0 / options.directions as f64;
+        let angle = horizon_elevation(source, position, azimuth, &horizon)?;
+        sum += angle.map_or(0.0, hidden);
+    }
+    Ok(1.0 - sum / options.directions as f64)
+}
+
+/// Returns the sky view factor of a grid cell,
+/// `None` for void cells or if `directions` is 0.
+///
+/// Rays are marched through the grid one cell size per step and end at
+/// `max_distance` or the border of the grid; terrain outside the grid is
+/// not seen. Void samples along a ray are skipped.
+pub fn sky_view_at<T: Sample>(
+    grid: &Grid<T>,
+    row: usize,
+    col: usize,
+    options: &SkyViewOptions,
+) -> Option<f64> {
+    let height = grid.get(row, col)?;
+    if options.directions == 0 {
+        return None;
+    }
+    let (dx, dy) = grid.cell_size(row);
+    let step = dx.min(dy);
+    let sum: f64 = (0..options.directions)
+        .map(|i| {
+            let azimuth = (i as f64 * 360.0 / options.directions as f64).to_radians();
+            let (east, north) = (libm::sin(azimuth), libm::cos(azimuth));
+            let mut steepest = 0.0f64;
+            for k in 1.. {
+                let distance = k as f64 * step;
+                let r = round_f64(row as f64 - distance * north / dy);
+                let c = round_f64(col as f64 + distance * east / dx);
+                if distance > options.max_distance
+                    || r < 0.0
+                    || c < 0.0
+                    || r >= grid.rows as f64
+                    || c >= grid.cols as f64
+                {
+                    break;
+                }
+                if let Some(terrain) = grid.get(r as usize, c as usize) {
+                    steepest = steepest.max((terrain - height) / distance);
+                }
+            }
+            hidden(libm::atan(steepest).to_degrees())
+        })
+        .sum();
+    Some(1.0 - sum / options.directions as f64)
+}
+
+/// Computes a sky view factor raster over a grid with [`sky_view_at`].
+///
+/// `out` must hold `rows * cols` cells, void cells are set to NaN.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - `directions` is 0 or `max_distance` is not positive.
+/// * `Error::BufferTooSmall` - `out` is too small.
+pub fn sky_view_grid<T: Sample>(
+    grid: &Grid<T>,
+    options: &SkyViewOptions,
+    out: &mut [f32],
+) -> Result<(), Error> {
+    if options.directions == 0 || options.max_distance.is_nan() || options.max_distance <= 0.0 {
+        return Err(Error::InvalidParameter);
+    }
+    if out.len() < grid.len() {
+        return Err(Error::BufferTooSmall);
+    }
+    for row in 0..grid.rows {
+        for col in 0..grid.cols {
+            out[row * grid.cols + col] =
+                sky_view_at(grid, row, col, options).map_or(f32::NAN, |value| value as f32);
+        }
+    }
+    Ok(())
+}
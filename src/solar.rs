@@ -0,0 +1,197 @@
+use super::Coord;
+use crate::{
+    ElevationSource, Error,
+    bitmap::{bitmap_len, set_bit},
+    grid::{Grid, Sample},
+    horizon::{HorizonOptions, horizon_elevation},
+    los::{Position, STANDARD_K_FACTOR, curvature_drop},
+    tiles::round_f64,
+};
+
+/// Position of the sun in the sky.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolarPosition {
+    /// Direction of the sun in degrees clockwise from north.
+    pub azimuth: f64,
+    /// Apparent angle of the sun above the horizon in degrees,
+    /// corrected for atmospheric refraction.
+    pub elevation: f64,
+}
+
+impl SolarPosition {
+    /// Returns `true` if the sun is above the horizontal plane.
+    pub fn is_up(&self) -> bool {
+        self.elevation > 0.0
+    }
+}
+
+/// Computes the position of the sun with the NOAA solar calculator
+/// algorithm, accurate to about 0.01° for years 1800 to 2100.
+///
+/// `timestamp` is the UTC time in seconds since 1970-01-01 (Unix time).
+pub fn solar_position(timestamp: i64, coord: impl Into<Coord>) -> SolarPosition {
+    let coord: Coord = coord.into();
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+    let julian_day = days as f64 + seconds as f64 / 86_400.0 + 2_440_587.5;
+    let t = (julian_day - 2_451_545.0) / 36_525.0;
+
+    let mean_longitude = (280.46646 + t * (36000.76983 + t * 0.0003032)) % 360.0;
+    let mean_anomaly = (357.52911 + t * (35999.05029 - 0.0001537 * t)).to_radians();
+    let eccentricity = 0.016708634 - t * (0.000042037 + 0.0000001267 * t);
+    let center = libm::sin(mean_anomaly) * (1.914602 - t * (0.004817 + 0.000014 * t))
+        + libm::sin(2.0 * mean_anomaly) * (0.019993 - 0.000101 * t)
+        + libm::sin(3.0 * mean_anomaly) * 0.000289;
+    let omega = (125.04 - 1934.136 * t).to_radians();
+    let apparent_longitude =
+        (mean_longitude + center - 0.00569 - 0.00478 * libm::sin(omega)).to_radians();
+    let mean_obliquity =
+        23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001813))) / 60.0) / 60.0;
+    let obliquity = (mean_obliquity + 0.00256 * libm::cos(omega)).to_radians();
+    let declination = libm::asin(libm::sin(obliquity) * libm::sin(apparent_longitude));
+
+    // equation of time in minutes
+    let y = libm::tan(obliquity / 2.0) * libm::tan(obliquity / 2.0);
+    let l = mean_longitude.to_radians();
+    let equation_of_time = 4.0
+        * (y * libm::sin(2.0 * l) - 2.0 * eccentricity * libm::sin(mean_anomaly)
+            + 4.0 * eccentricity * y * libm::sin(mean_anomaly) * libm::cos(2.0 * l)
+            - 0.5 * y * y * libm::sin(4.0 * l)
+            - 1.25 * eccentricity * eccentricity * libm::sin(2.0 * mean_anomaly))
+        .to_degrees();
+    let solar_time = seconds as f64 / 60.0 + equation_of_time + 4.0 * coord.lon;
+    let hour_angle = (solar_time / 4.0 - 180.0).to_radians();
+
+    let lat = coord.lat.to_radians();
+    let cos_zenith = libm::sin(lat) * libm::sin(declination)
+        + libm::cos(lat) * libm::cos(declination) * libm::cos(hour_angle);
+    let elevation = 90.0 - libm::acos(cos_zenith.clamp(-1.0, 1.0)).to_degrees();
+    let azimuth = libm::atan2(
+        libm::sin(hour_angle),
+        libm::cos(hour_angle) * libm::sin(lat) - libm::tan(declination) * libm::cos(lat),
+    )
+    .to_degrees()
+        + 180.0;
+    SolarPosition {
+        azimuth: azimuth % 360.0,
+        elevation: elevation + refraction(elevation),
+    }
+}
+
+/// Atmospheric refraction in degrees for a true elevation in degrees.
+fn refraction(elevation: f64) -> f64 {
+    let tan = libm::tan(elevation.to_radians());
+    let arc_seconds = if elevation > 85.0 {
+        0.0
+    } else if elevation > 5.0 {
+        58.1 / tan - 0.07 / (tan * tan * tan) + 0.000086 / (tan * tan * tan * tan * tan)
+    } else if elevation > -0.575 {
+        1735.0
+            + elevation * (-518.2 + elevation * (103.4 + elevation * (-12.79 + elevation * 0.711)))
+    } else {
+        -20.772 / tan
+    };
+    arc_seconds / 3600.0
+}
+
+/// Marks the cells of a grid lying in the shadow of the terrain.
+///
+/// From every cell a ray is followed towards the sun, one cell size per step,
+/// until it rises above the highest sample of the grid or leaves the grid;
+/// the cell is shadowed if the terrain along the ray is above it. The earth
+/// curvature is applied with standard refraction. Terrain outside the grid
+/// is not known, so rays leaving the grid count as sunlit. With the sun at or
+/// below the horizon all cells are shadowed; void cells are never shadowed.
+///
+/// `bitmap` is written like the bitmap of a [`crate::viewshed::Viewshed`]
+/// and must hold [`crate::bitmap::bitmap_len`] bytes. Returns the number
+/// of shadowed cells.
+///
+/// # Errors
+///
+/// * `Error::BufferTooSmall` - `bitmap` is too small.
+pub fn shadow_mask<T: Sample>(
+    grid: &Grid<T>,
+    sun: &SolarPosition,
+    bitmap: &mut [u8],
+) -> Result<usize, Error> {
+    let len = bitmap_len(grid.rows, grid.cols);
+    if bitmap.len() < len {
+        return Err(Error::BufferTooSmall);
+    }
+    bitmap[..len].fill(0);
+    let highest = grid.heights[..grid.len()]
+        .iter()
+        .filter_map(|value| value.value())
+        .fold(f64::NEG_INFINITY, f64::max);
+    let (east, north) = (
+        libm::sin(sun.azimuth.to_radians()),
+        libm::cos(sun.azimuth.to_radians()),
+    );
+    let rise = libm::tan(sun.elevation.to_radians());
+
+    let mut shadowed = 0;
+    for row in 0..grid.rows {
+        for col in 0..grid.cols {
+            let Some(height) = grid.get(row, col) else {
+                continue;
+            };
+            if !sun.is_up() || in_shadow(grid, (row, col), height, (east, north), rise, highest) {
+                set_bit(bitmap, row * grid.cols + col);
+                shadowed += 1;
+            }
+        }
+    }
+    Ok(shadowed)
+}
+
+/// Follows the ray from a cell towards the sun.
+fn in_shadow<T: Sample>(
+    grid: &Grid<T>,
+    (row, col): (usize, usize),
+    height: f64,
+    (east, north): (f64, f64),
+    rise: f64,
+    highest: f64,
+) -> bool {
+    let (dx, dy) = grid.cell_size(row);
+    let step = dx.min(dy);
+    for i in 1.. {
+        let distance = i as f64 * step;
+        let ray = height + distance * rise;
+        if ray > highest {
+            return false;
+        }
+        let r = round_f64(row as f64 - distance * north / dy);
+        let c = round_f64(col as f64 + distance * east / dx);
+        if r < 0.0 || c < 0.0 || r >= grid.rows as f64 || c >= grid.cols as f64 {
+            return false;
+        }
+        let terrain = grid.get(r as usize, c as usize);
+        if terrain
+            .is_some_and(|terrain| terrain - curvature_drop(distance, STANDARD_K_FACTOR) > ray)
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Checks whether the sun shines on a position at the given time.
+///
+/// The sun must be above the terrain horizon in its direction, which is
+/// scanned with [`horizon_elevation`] using `options`; see there for the
+/// errors. `timestamp` is given as for [`solar_position`].
+pub fn is_sunlit<S: ElevationSource>(
+    source: &mut S,
+    observer: Position,
+    timestamp: i64,
+    options: &HorizonOptions,
+) -> Result<bool, Error> {
+    let sun = solar_position(timestamp, observer.coord);
+    if !sun.is_up() {
+        return Ok(false);
+    }
+    let horizon = horizon_elevation(source, observer, sun.azimuth, options)?;
+    Ok(horizon.is_none_or(|horizon| sun.elevation > horizon))
+}
@@ -0,0 +1,242 @@
+use super::Coord;
+use crate::{
+    ElevationSource, Error,
+    grid::{Grid, Sample, snap},
+};
+
+/// Slope and aspect of the terrain at one cell.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct SlopeAspect {
+    /// Slope angle in degrees, 0 is flat.
+    pub slope: f64,
+    /// Direction the slope faces in degrees clockwise from north
+    /// (downhill direction), `None` on flat terrain.
+    pub aspect: Option<f64>,
+}
+
+impl SlopeAspect {
+    /// Returns the slope as percent rise (100 % = 45°).
+    pub fn slope_percent(&self) -> f64 {
+        libm::tan(self.slope.to_radians()) * 100.0
+    }
+}
+
+/// Computes slope and aspect with Horn's method.
+///
+/// `z` is the 3x3 neighbourhood row by row starting in the north-west, as
+/// returned by [`Grid::neighbourhood`]; `dx` and `dy` are the east-west and
+/// north-south cell sizes in meters.
+pub fn horn(z: &[f64; 9], dx: f64, dy: f64) -> SlopeAspect {
+    let (dz_dx, dz_dy) = horn_gradient(z, dx, dy);
+    let slope = libm::atan(libm::sqrt(dz_dx * dz_dx + dz_dy * dz_dy)).to_degrees();
+    let aspect = if dz_dx == 0.0 && dz_dy == 0.0 {
+        None
+    } else {
+        // the terrain faces against the gradient
+        Some((libm::atan2(-dz_dx, -dz_dy).to_degrees() + 360.0) % 360.0)
+    };
+    SlopeAspect { slope, aspect }
+}
+
+/// Returns Horn's height gradient (towards east, towards north) in meters per meter.
+pub(crate) fn horn_gradient(z: &[f64; 9], dx: f64, dy: f64) -> (f64, f64) {
+    let dz_dx = ((z[2] + 2.0 * z[5] + z[8]) - (z[0] + 2.0 * z[3] + z[6])) / (8.0 * dx);
+    let dz_dy = ((z[0] + 2.0 * z[1] + z[2]) - (z[6] + 2.0 * z[7] + z[8])) / (8.0 * dy);
+    (dz_dx, dz_dy)
+}
+
+/// Returns slope and aspect of a grid cell,
+/// `None` at the border of the grid or next to void samples.
+pub fn slope_aspect_at<T: Sample>(grid: &Grid<T>, row: usize, col: usize) -> Option<SlopeAspect> {
+    let z = grid.neighbourhood(row, col)?;
+    let (dx, dy) = grid.cell_size(row);
+    Some(horn(&z, dx, dy))
+}
+
+/// Returns slope and aspect at the sample nearest to the coordinate.
+///
+/// The 3x3 neighbourhood is read from the source, so near tile borders a
+/// [`crate::Mosaic`] is needed.
+///
+/// # Errors
+///
+/// * `Error::InvalidData` - one of the nine samples is void.
+/// * any error of the source.
+pub fn slope_aspect<S: ElevationSource>(
+    source: &mut S,
+    coord: impl Into<Coord>,
+) -> Result<SlopeAspect, Error> {
+    let mut buffer = [0i16; 9];
+    let grid = read_neighbourhood(source, coord.into(), &mut buffer)?;
+    slope_aspect_at(&grid, 1, 1).ok_or(Error::InvalidData)
+}
+
+/// Reads the 3x3 samples around the sample nearest to the coordinate.
+fn read_neighbourhood<'a, S: ElevationSource>(
+    source: &mut S,
+    coord: Coord,
+    buffer: &'a mut [i16; 9],
+) -> Result<Grid<'a>, Error> {
+    let resolution = source.resolution();
+    let center = snap(coord, resolution);
+    let step = resolution.step();
+    Grid::read(source, (center.lat + step, center.lon - step), 3, 3, buffer)
+}
+
+/// Computes slope and aspect rasters over a grid.
+///
+/// `slope` receives the slope in degrees, `aspect` the aspect in degrees
+/// clockwise from north; both must hold `rows * cols` cells. Border cells,
+/// cells next to void samples and (for `aspect`) flat cells are set to NaN.
+pub fn slope_aspect_grid<T: Sample>(
+    grid: &Grid<T>,
+    slope: &mut [f32],
+    aspect: &mut [f32],
+) -> Result<(), Error> {
+    if slope.len() < grid.len() || aspect.len() < grid.len() {
+        return Err(Error::BufferTooSmall);
+    }
+    for row in 0..grid.rows {
+        for col in 0..grid.cols {
+            let index = row * grid.cols + col;
+            let value = slope_aspect_at(grid, row, col);
+            slope[index] = value.map_or(f32::NAN, |value| value.slope as f32);
+            aspect[index] = value
+                .and_then(|value| value.aspect)
+                .map_or(f32::NAN, |aspect| aspect as f32);
+        }
+    }
+    Ok(())
+}
+
+/// The terrain metrics of [`TerrainIndices`], to select one for a raster.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerrainIndex {
+    Tri,
+    Tpi,
+    Roughness,
+    ProfileCurvature,
+    PlanCurvature,
+}
+
+/// Neighbourhood metrics describing the shape of the terrain at one cell.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct TerrainIndices {
+    /// Terrain ruggedness index (Riley et al.), the root of the summed
+    /// squared height differences to the eight neighbours, in meters.
+    pub tri: f64,
+    /// Topographic position index, the height above the mean of the eight
+    /// neighbours in meters; positive on ridges, negative in valleys.
+    pub tpi: f64,
+    /// Largest height difference within the neighbourhood in meters.
+    pub roughness: f64,
+    /// Curvature in the direction of the slope in 1/m; positive where the
+    /// slope flattens downhill (concave), negative where it steepens.
+    /// 0 on flat terrain.
+    pub profile_curvature: f64,
+    /// Curvature across the slope in 1/m; positive in hollows where flow
+    /// converges, negative on spurs where it diverges. 0 on flat terrain.
+    pub plan_curvature: f64,
+}
+
+impl TerrainIndices {
+    /// Returns the value of one metric.
+    pub fn get(&self, index: TerrainIndex) -> f64 {
+        match index {
+            TerrainIndex::Tri => self.tri,
+            TerrainIndex::Tpi => self.tpi,
+            TerrainIndex::Roughness => self.roughness,
+            TerrainIndex::ProfileCurvature => self.profile_curvature,
+            TerrainIndex::PlanCurvature => self.plan_curvature,
+        }
+    }
+}
+
+/// Computes the terrain indices of a 3x3 neighbourhood.
+///
+/// `z`, `dx` and `dy` are given as for [`horn`]. The curvatures are derived
+/// from the quadratic surface of Zevenbergen and Thorne.
+pub fn neighbourhood_indices(z: &[f64; 9], dx: f64, dy: f64) -> TerrainIndices {
+    let center = z[4];
+    let (mut sum, mut squares) = (0.0, 0.0);
+    let (mut min, mut max) = (center, center);
+    for (i, value) in z.iter().enumerate() {
+        min = min.min(*value);
+        max = max.max(*value);
+        if i != 4 {
+            sum += value;
+            squares += (value - center) * (value - center);
+        }
+    }
+
+    // second derivatives (d2 = 2D, e2 = 2E, f) and gradient (g, h)
+    let d2 = (z[3] + z[5] - 2.0 * center) / (dx * dx);
+    let e2 = (z[1] + z[7] - 2.0 * center) / (dy * dy);
+    let f = (z[2] - z[0] + z[6] - z[8]) / (4.0 * dx * dy);
+    let g = (z[5] - z[3]) / (2.0 * dx);
+    let h = (z[1] - z[7]) / (2.0 * dy);
+    let gradient = g * g + h * h;
+    let (profile_curvature, plan_curvature) = if gradient == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (
+            (d2 * g * g + e2 * h * h + 2.0 * f * g * h) / gradient,
+            (d2 * h * h + e2 * g * g - 2.0 * f * g * h) / gradient,
+        )
+    };
+    TerrainIndices {
+        tri: libm::sqrt(squares),
+        tpi: center - sum / 8.0,
+        roughness: max - min,
+        profile_curvature,
+        plan_curvature,
+    }
+}
+
+/// Returns the terrain indices of a grid cell,
+/// `None` at the border of the grid or next to void samples.
+pub fn terrain_indices_at<T: Sample>(
+    grid: &Grid<T>,
+    row: usize,
+    col: usize,
+) -> Option<TerrainIndices> {
+    let z = grid.neighbourhood(row, col)?;
+    let (dx, dy) = grid.cell_size(row);
+    Some(neighbourhood_indices(&z, dx, dy))
+}
+
+/// Returns the terrain indices at the sample nearest to the coordinate.
+///
+/// # Errors
+///
+/// * `Error::InvalidData` - one of the nine samples is void.
+/// * any error of the source.
+pub fn terrain_indices<S: ElevationSource>(
+    source: &mut S,
+    coord: impl Into<Coord>,
+) -> Result<TerrainIndices, Error> {
+    let mut buffer = [0i16; 9];
+    let grid = read_neighbourhood(source, coord.into(), &mut buffer)?;
+    terrain_indices_at(&grid, 1, 1).ok_or(Error::InvalidData)
+}
+
+/// Computes a raster of one terrain index over a grid.
+///
+/// `out` must hold `rows * cols` cells. Border cells and cells next to void
+/// samples are set to NaN.
+pub fn terrain_index_grid<T: Sample>(
+    grid: &Grid<T>,
+    index: TerrainIndex,
+    out: &mut [f32],
+) -> Result<(), Error> {
+    if out.len() < grid.len() {
+        return Err(Error::BufferTooSmall);
+    }
+    for row in 0..grid.rows {
+        for col in 0..grid.cols {
+            out[row * grid.cols + col] = terrain_indices_at(grid, row, col)
+                .map_or(f32::NAN, |indices| indices.get(index) as f32);
+        }
+    }
+    Ok(())
+}
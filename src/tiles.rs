@@ -1,5 +1,5 @@
 /// Custom round function for f64 in no_std environments
-fn round_f64(x: f64) -> f64 {
+pub(crate) fn round_f64(x: f64) -> f64 {
     let i = if x < 0.0 {
         (x - 0.5) as i64
     } else {
@@ -9,18 +9,26 @@ fn round_f64(x: f64) -> f64 {
 }
 
 use super::Coord;
-use crate::{Error, HgtReader, resolutions::Resolution};
+use crate::{Bounds, ElevationSource, Error, HgtReader, resolutions::Resolution};
+
+/// Height value used by HGT files to mark samples without data.
+pub const VOID_VALUE: i16 = -32768;
+
+/// Size of the stack buffer used to convert window rows, in bytes.
+const WINDOW_CHUNK: usize = 256;
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Tile<R: HgtReader> {
-    /// north-south position of the [`Tile`]
+    /// north-south position of the [`Tile`] (south-west corner of the last opened file)
     /// angle, ranges from −90° (south pole) to 90° (north pole), 0° is the Equator
     pub latitude: i8,
-    /// east-west position of the [`Tile`]
+    /// east-west position of the [`Tile`] (south-west corner of the last opened file)
     /// angle, ranges from -180° to 180°
     pub longitude: i16,
     pub resolution: Resolution,
     data_reader: R,
+    /// `true` once a file has been opened successfully.
+    loaded: bool,
 }
 
 impl<R: HgtReader> Tile<R> {
@@ -30,7 +38,75 @@ impl<R: HgtReader> Tile<R> {
             data_reader: reader,
             latitude: 0,
             longitude: 0,
+            loaded: false,
+        }
+    }
+
+    /// Returns a reference to the reader used by the tile.
+    pub fn reader(&self) -> &R {
+        &self.data_reader
+    }
+
+    /// Returns the south-west corner (latitude, longitude) of the tile
+    /// currently opened, or `None` if no file has been opened yet.
+    pub fn origin(&self) -> Option<(i8, i16)> {
+        self.loaded.then_some((self.latitude, self.longitude))
+    }
+
+    /// Returns the area covered by the tile currently opened,
+    /// or `None` if no file has been opened yet.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.origin().map(|(lat, lon)| Bounds {
+            south: lat as f64,
+            west: lon as f64,
+            north: lat as f64 + 1.0,
+            east: lon as f64 + 1.0,
+        })
+    }
+
+    /// Returns `true` if the coordinate lies within the tile currently opened.
+    ///
+    /// Coordinates on the edges are contained, since HGT files include the
+    /// edge rows and columns they share with their neighbours.
+    pub fn contains(&self, coord: impl Into<Coord>) -> bool {
+        self.bounds().is_some_and(|bounds| bounds.contains(coord))
+    }
+
+    /// Converts a sample position of the tile currently opened into its coordinate.
+    ///
+    /// Row 0 is the northern edge, column 0 the western edge of the tile.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotFound` - no file has been opened yet.
+    /// * `Error::IndexOutOfBounds` - row or column lie outside the tile.
+    pub fn sample_to_coord(&self, row: usize, col: usize) -> Result<Coord, Error> {
+        let (lat, lon) = self.origin().ok_or(Error::NotFound)?;
+        let res_size = self.resolution.point_per_degree();
+        if row >= res_size || col >= res_size {
+            return Err(Error::IndexOutOfBounds);
         }
+        let step = 1.0 / (res_size as f64 - 1.0);
+        Ok(Coord {
+            lat: lat as f64 + 1.0 - row as f64 * step,
+            lon: lon as f64 + col as f64 * step,
+        })
+    }
+
+    /// Converts a coordinate into the (row, column) of the nearest sample
+    /// of the tile currently opened.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotFound` - no file has been opened yet.
+    /// * `Error::IndexOutOfBounds` - the coordinate lies outside the tile.
+    pub fn coord_to_sample(&self, coord: impl Into<Coord>) -> Result<(usize, usize), Error> {
+        let coord: Coord = coord.into();
+        let (lat, lon) = self.origin().ok_or(Error::NotFound)?;
+        if !self.contains(coord) {
+            return Err(Error::IndexOutOfBounds);
+        }
+        Ok(self.sample_index(coord, (lat, lon)))
     }
     /// Retrieves the height for the specified coordinate from the HGT data file.
     ///
@@ -53,23 +129,11 @@ impl<R: HgtReader> Tile<R> {
     ///
     /// * `Option<i16>` - The height value if successful, or `None` if an error
     ///   occurs or if the height data is invalid.
-
     pub fn get_height<Reader: HgtReader>(&mut self, coord: impl Into<Coord>) -> Result<i16, Error> {
         let coord: Coord = coord.into();
-        let filename = coord.get_filename();
-
-        self.data_reader
-            .open_hgt_file(filename.as_str())
-            .and_then(|_| {
-                self.data_reader
-                    .check_hgt_file(self.resolution.expected_file_length() as u64)
-            })?;
-        let coord_trunc = coord.trunc();
+        let origin = self.open_for(coord)?;
+        let (row, col) = self.sample_index(coord, origin);
         let res_size = self.resolution.point_per_degree();
-        let lat_diff: f64 = (1.0 - (coord.lat - coord_trunc.0 as f64)) * (res_size as f64 - 1.0);
-        let lon_diff = (coord.lon - coord_trunc.1 as f64) * (res_size as f64 - 1.0);
-        let row = round_f64(lat_diff) as usize;
-        let col = round_f64(lon_diff) as usize;
         let index = (row * res_size + col) * 2;
 
         if index >= self.resolution.expected_file_length() {
@@ -79,10 +143,166 @@ impl<R: HgtReader> Tile<R> {
         self.data_reader.read_hgt_data(index as u64, &mut buffer)?;
 
         let height = i16::from_be_bytes(buffer);
-        if height == -32768 {
+        if height == VOID_VALUE {
             Err(Error::InvalidData)
         } else {
             Ok(height)
         }
     }
+
+    /// Reads a rectangular window of heights from the HGT data file.
+    ///
+    /// The window starts at the sample nearest to `coord` (its north-west
+    /// corner) and extends `rows` samples to the south and `cols` samples to
+    /// the east. The heights are written row by row into `buffer`, which must
+    /// hold at least `rows * cols` values. Rows are fetched with
+    /// [`HgtReader::read_hgt_block`] instead of one read per sample.
+    ///
+    /// Void samples are not filtered; they are returned as [`VOID_VALUE`].
+    ///
+    /// # Errors
+    ///
+    /// * `Error::BufferTooSmall` - `buffer` cannot hold the window.
+    /// * `Error::IndexOutOfBounds` - the window does not fit into the tile.
+    /// * any error returned by the reader.
+    pub fn read_window(
+        &mut self,
+        coord: impl Into<Coord>,
+        rows: usize,
+        cols: usize,
+        buffer: &mut [i16],
+    ) -> Result<(), Error> {
+        let coord: Coord = coord.into();
+        if rows.checked_mul(cols).is_none_or(|len| buffer.len() < len) {
+            return Err(Error::BufferTooSmall);
+        }
+        let origin = self.open_for(coord)?;
+        let (row, col) = self.sample_index(coord, origin);
+        let res_size = self.resolution.point_per_degree();
+        if row.saturating_add(rows) > res_size || col.saturating_add(cols) > res_size {
+            return Err(Error::IndexOutOfBounds);
+        }
+
+        for r in 0..rows {
+            self.read_row(row + r, col, &mut buffer[r * cols..(r + 1) * cols])?;
+        }
+        Ok(())
+    }
+
+    /// Reads `out.len()` consecutive samples of one row of the tile currently
+    /// opened, starting at `col`.
+    pub(crate) fn read_row(
+        &mut self,
+        row: usize,
+        col: usize,
+        out: &mut [i16],
+    ) -> Result<(), Error> {
+        let res_size = self.resolution.point_per_degree();
+        if !self.loaded {
+            return Err(Error::NotFound);
+        }
+        if row >= res_size || col + out.len() > res_size {
+            return Err(Error::IndexOutOfBounds);
+        }
+        let mut bytes = [0u8; WINDOW_CHUNK];
+        let row_start = (row * res_size + col) * 2;
+        for (c, out) in out.chunks_mut(WINDOW_CHUNK / 2).enumerate() {
+            let bytes = &mut bytes[..out.len() * 2];
+            let pos = row_start + c * WINDOW_CHUNK;
+            self.data_reader.read_hgt_block(pos as u64, bytes)?;
+            for (height, pair) in out.iter_mut().zip(bytes.chunks_exact(2)) {
+                *height = i16::from_be_bytes([pair[0], pair[1]]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens the HGT file covering `coord`, checks its size and records its origin.
+    ///
+    /// Samples on the edge shared by two files are served by the file opened
+    /// already if it contains them, otherwise by the file the coordinate lies
+    /// in or, if that cannot be opened, by its neighbour sharing the edge.
+    fn open_for(&mut self, coord: Coord) -> Result<(i8, i16), Error> {
+        let n = self.resolution.point_per_degree() as i64 - 1;
+        let gy = round_f64(coord.lat * n as f64) as i64;
+        let gx = round_f64(coord.lon * n as f64) as i64;
+        let (lat, lon) = coord.tile_origin();
+        // tiles whose samples include the one nearest to `coord`
+        let lats = [lat as i64, gy.div_euclid(n), gy.div_euclid(n) - 1];
+        let lons = [lon as i64, gx.div_euclid(n), gx.div_euclid(n) - 1];
+        let contains = |(lat, lon): (i64, i64)| {
+            lat * n <= gy && gy <= (lat + 1) * n && lon * n <= gx && gx <= (lon + 1) * n
+        };
+        if let Some(origin) = self.origin()
+            && contains((origin.0 as i64, origin.1 as i64))
+        {
+            return Ok(origin);
+        }
+        let mut result = Err(Error::NotFound);
+        let mut tried = heapless::Vec::<(i64, i64), 9>::new();
+        for lat in lats {
+            for lon in lons {
+                if tried.contains(&(lat, lon))
+                    || !contains((lat, lon))
+                    || !(-90..90).contains(&lat)
+                    || !(-180..180).contains(&lon)
+                {
+                    continue;
+                }
+                tried.push((lat, lon)).ok();
+                let origin = (lat as i8, lon as i16);
+                result = self.open_tile(origin).map(|_| origin);
+                if result.is_ok() {
+                    return result;
+                }
+            }
+        }
+        result
+    }
+
+    /// Opens the HGT file whose south-west corner is `origin`,
+    /// checks its size and records the origin.
+    pub(crate) fn open_tile(&mut self, origin: (i8, i16)) -> Result<(), Error> {
+        let filename = Coord::new(origin.0, origin.1).get_filename();
+        let result = self
+            .data_reader
+            .open_hgt_file(filename.as_str())
+            .and_then(|_| {
+                self.data_reader
+                    .check_hgt_file(self.resolution.expected_file_length() as u64)
+            });
+        self.loaded = result.is_ok();
+        result?;
+        (self.latitude, self.longitude) = origin;
+        Ok(())
+    }
+
+    /// Returns the row and column of the sample nearest to `coord`
+    /// within the tile starting at `origin`.
+    fn sample_index(&self, coord: Coord, origin: (i8, i16)) -> (usize, usize) {
+        let res_size = self.resolution.point_per_degree();
+        let lat_diff: f64 = (1.0 - (coord.lat - origin.0 as f64)) * (res_size as f64 - 1.0);
+        let lon_diff = (coord.lon - origin.1 as f64) * (res_size as f64 - 1.0);
+        (round_f64(lat_diff) as usize, round_f64(lon_diff) as usize)
+    }
+}
+
+impl<R: HgtReader> ElevationSource for Tile<R> {
+    fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    fn height_at(&mut self, coord: Coord) -> Result<i16, Error> {
+        self.get_height::<R>(coord)
+    }
+
+    fn read_window(
+        &mut self,
+        coord: Coord,
+        rows: usize,
+        cols: usize,
+        buffer: &mut [i16],
+    ) -> Result<(), Error> {
+        Tile::read_window(self, coord, rows, cols, buffer)
+    }
 }
@@ -0,0 +1,140 @@
+use super::Coord;
+use crate::{ElevationSource, Error};
+
+/// Parameters of [`track_stats`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackOptions {
+    /// Height change in meters that must be exceeded before it counts as
+    /// ascent or descent, suppressing the noise of the terrain model.
+    pub hysteresis: f64,
+    /// Minimum horizontal distance in meters over which the grade is measured.
+    pub grade_distance: f64,
+}
+
+impl Default for TrackOptions {
+    /// 5 m hysteresis and grades over at least 100 m.
+    fn default() -> Self {
+        TrackOptions {
+            hysteresis: 5.0,
+            grade_distance: 100.0,
+        }
+    }
+}
+
+/// Statistics of a track computed by [`track_stats`].
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct TrackStats {
+    /// Horizontal length of the track in meters.
+    pub distance: f64,
+    /// Length of the track along the terrain surface in meters.
+    pub surface_distance: f64,
+    /// Sum of the height gains in meters after hysteresis.
+    pub total_ascent: f64,
+    /// Sum of the height losses in meters after hysteresis.
+    pub total_descent: f64,
+    /// Steepest uphill grade in percent, `None` if no stretch was long enough.
+    pub max_grade: Option<f64>,
+    /// Steepest downhill grade in percent as a negative value,
+    /// `None` if no stretch was long enough.
+    pub min_grade: Option<f64>,
+}
+
+/// Computes the distance, climb and grades of a track from terrain heights.
+///
+/// The height of every track point is interpolated from the source. The
+/// surface distance adds the height difference to each leg between points
+/// with valid heights. The track only turns from climbing to descending or
+/// back once the height changes more than `hysteresis` from the last highest
+/// or lowest point, so smaller bumps are not counted. Grades are measured
+/// over consecutive stretches of at least `grade_distance` meters. Void
+/// points count for the distance only.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the track is empty, the hysteresis is negative or the grade distance is not positive.
+/// * any error of the source except `Error::InvalidData`.
+///
+/// # Example
+/// ```ignore
+/// let track = [Coord::new(47.42, 10.98), Coord::new(47.43, 10.99), Coord::new(47.44, 10.99)];
+/// let stats = track_stats(&mut tile, &track, &TrackOptions::default())?;
+/// ```
+pub fn track_stats<S: ElevationSource>(
+    source: &mut S,
+    track: &[Coord],
+    options: &TrackOptions,
+) -> Result<TrackStats, Error> {
+    if track.is_empty()
+        || options.hysteresis < 0.0
+        || options.hysteresis.is_nan()
+        || options.grade_distance <= 0.0
+        || options.grade_distance.is_nan()
+    {
+        return Err(Error::InvalidParameter);
+    }
+    let mut stats = TrackStats::default();
+    // last point and its height
+    let mut last: Option<f64> = None;
+    // whether the track climbs, `None` until the first change beyond the
+    // hysteresis, and the highest or lowest height since the last turn
+    let mut rising: Option<bool> = None;
+    let mut extreme: Option<f64> = None;
+    // start of the current grade stretch as distance and height
+    let mut stretch: Option<(f64, f64)> = None;
+
+    for (i, &coord) in track.iter().enumerate() {
+        let leg = if i > 0 {
+            track[i - 1].distance_to(coord)
+        } else {
+            0.0
+        };
+        stats.distance += leg;
+        let height = match source.interpolated_height(coord) {
+            Ok(height) => Some(height),
+            Err(Error::InvalidData) => None,
+            Err(err) => return Err(err),
+        };
+        let rise = match (last, height) {
+            (Some(last), Some(height)) => height - last,
+            _ => 0.0,
+        };
+        stats.surface_distance += libm::sqrt(leg * leg + rise * rise);
+        last = height;
+        let Some(height) = height else {
+            continue;
+        };
+
+        match (rising, extreme) {
+            (_, None) => extreme = Some(height),
+            (Some(true), Some(top)) if height > top => {
+                stats.total_ascent += height - top;
+                extreme = Some(height);
+            }
+            (Some(false), Some(bottom)) if height < bottom => {
+                stats.total_descent += bottom - height;
+                extreme = Some(height);
+            }
+            (Some(false) | None, Some(turn)) if height - turn > options.hysteresis => {
+                stats.total_ascent += height - turn;
+                (rising, extreme) = (Some(true), Some(height));
+            }
+            (Some(true) | None, Some(turn)) if turn - height > options.hysteresis => {
+                stats.total_descent += turn - height;
+                (rising, extreme) = (Some(false), Some(height));
+            }
+            _ => {}
+        }
+
+        match stretch {
+            Some((start, base)) if stats.distance - start >= options.grade_distance => {
+                let grade = (height - base) / (stats.distance - start) * 100.0;
+                stats.max_grade = Some(stats.max_grade.map_or(grade, |max| max.max(grade)));
+                stats.min_grade = Some(stats.min_grade.map_or(grade, |min| min.min(grade)));
+                stretch = Some((stats.distance, height));
+            }
+            Some(_) => {}
+            None => stretch = Some((stats.distance, height)),
+        }
+    }
+    Ok(stats)
+}
@@ -0,0 +1,168 @@
+use super::Coord;
+pub use crate::bitmap::{bitmap_len, get_bit};
+use crate::{
+    ElevationSource, Error,
+    bitmap::set_bit,
+    grid::snap,
+    los::{STANDARD_K_FACTOR, curvature_drop},
+    resolutions::Resolution,
+    tiles::round_f64,
+};
+
+/// Parameters of [`viewshed`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewshedOptions {
+    /// Eye height of the observer above the terrain in meters.
+    pub observer_height: f64,
+    /// Height above the terrain a cell must be seen at, in meters.
+    pub target_height: f64,
+    /// Take the curvature of the earth into account.
+    pub earth_curvature: bool,
+    /// Refraction coefficient, only used together with `earth_curvature`.
+    pub k_factor: f64,
+}
+
+impl Default for ViewshedOptions {
+    /// Observer at 1.7 m, targets on the ground, standard refraction.
+    fn default() -> Self {
+        ViewshedOptions {
+            observer_height: 1.7,
+            target_height: 0.0,
+            earth_curvature: true,
+            k_factor: STANDARD_K_FACTOR,
+        }
+    }
+}
+
+/// Placement of a visibility bitmap written by [`viewshed`].
+///
+/// The bitmap covers `rows` x `cols` cells around the observer, row by row
+/// from the north-west; cell `i` is bit `i % 8` of byte `i / 8`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewshed {
+    /// Position of the cell in row 0, column 0.
+    pub origin: Coord,
+    pub rows: usize,
+    pub cols: usize,
+    /// Number of visible cells.
+    pub visible: usize,
+}
+
+impl Viewshed {
+    /// Returns `true` if the cell is marked visible in the bitmap.
+    pub fn is_visible(&self, bitmap: &[u8], row: usize, col: usize) -> bool {
+        row < self.rows && col < self.cols && get_bit(bitmap, row * self.cols + col)
+    }
+}
+
+/// Returns the number of rows and columns of the viewshed around an observer
+/// at latitude `lat` for the given radius in meters.
+pub fn dimensions(resolution: Resolution, lat: f64, radius: f64) -> (usize, usize) {
+    let (dx, dy) = resolution.cell_size(lat);
+    let (half_rows, half_cols) = ((radius / dy) as usize, (radius / dx) as usize);
+    let size = |half: usize| half.saturating_mul(2).saturating_add(1);
+    (size(half_rows), size(half_cols))
+}
+
+/// Computes which cells within `radius` meters are visible from the observer.
+///
+/// Uses the R2 algorithm: rays are cast from the observer to every cell on
+/// the border of the area, and each cell along a ray is visible if its target
+/// point is not below the steepest sight line to the terrain passed so far.
+/// A cell is visible if any ray sees it. Cells outside the radius and void
+/// cells are marked invisible.
+///
+/// `bitmap` must hold [`bitmap_len`] bytes for the [`dimensions`] of the
+/// area. Heights are read cell by cell, so wrapping the reader in a
+/// [`crate::CachedReader`] is recommended.
+///
+/// # Errors
+///
+/// * `Error::InvalidParameter` - the radius is not positive.
+/// * `Error::BufferTooSmall` - `bitmap` is too small.
+/// * `Error::InvalidData` - the terrain at the observer is void.
+/// * any error of the source except `Error::InvalidData`.
+pub fn viewshed<S: ElevationSource>(
+    source: &mut S,
+    observer: impl Into<Coord>,
+    radius: f64,
+    options: &ViewshedOptions,
+    bitmap: &mut [u8],
+) -> Result<Viewshed, Error> {
+    if radius <= 0.0 {
+        return Err(Error::InvalidParameter);
+    }
+    let resolution = source.resolution();
+    let center = snap(observer.into(), resolution);
+    let (rows, cols) = dimensions(resolution, center.lat, radius);
+    if bitmap.len() < bitmap_len(rows, cols) {
+        return Err(Error::BufferTooSmall);
+    }
+    bitmap[..bitmap_len(rows, cols)].fill(0);
+
+    let step = resolution.step();
+    let (half_rows, half_cols) = (rows / 2, cols / 2);
+    let origin = Coord {
+        lat: center.lat + half_rows as f64 * step,
+        lon: center.lon - half_cols as f64 * step,
+    };
+    let (dx, dy) = resolution.cell_size(center.lat);
+    let eye = source.height_at(center)? as f64 + options.observer_height;
+    set_bit(bitmap, half_rows * cols + half_cols);
+
+    let mut view = Viewshed {
+        origin,
+        rows,
+        cols,
+        visible: 1,
+    };
+    let mut ray_to = |row: usize, col: usize| -> Result<(), Error> {
+        let (d_row, d_col) = (row as f64 - half_rows as f64, col as f64 - half_cols as f64);
+        let steps = d_row.abs().max(d_col.abs()) as usize;
+        // steepest sight line to the terrain so far
+        let mut max_slope = f64::NEG_INFINITY;
+        for i in 1..=steps {
+            let t = i as f64 / steps as f64;
+            let r = round_f64(half_rows as f64 + t * d_row) as usize;
+            let c = round_f64(half_cols as f64 + t * d_col) as usize;
+            let (north, east) = (
+                (half_rows as f64 - r as f64) * dy,
+                (c as f64 - half_cols as f64) * dx,
+            );
+            let distance = libm::sqrt(north * north + east * east);
+            if distance > radius {
+                break;
+            }
+            let coord = Coord {
+                lat: origin.lat - r as f64 * step,
+                lon: origin.lon + c as f64 * step,
+            };
+            let mut terrain = match source.height_at(coord) {
+                Ok(height) => height as f64,
+                Err(Error::InvalidData) => continue,
+                Err(err) => return Err(err),
+            };
+            if options.earth_curvature {
+                terrain -= curvature_drop(distance, options.k_factor);
+            }
+            let target_slope = (terrain + options.target_height - eye) / distance;
+            let index = r * cols + c;
+            if target_slope >= max_slope && !get_bit(bitmap, index) {
+                set_bit(bitmap, index);
+                view.visible += 1;
+            }
+            max_slope = max_slope.max((terrain - eye) / distance);
+        }
+        Ok(())
+    };
+
+    for col in 0..cols {
+        ray_to(0, col)?;
+        ray_to(rows - 1, col)?;
+    }
+    for row in 1..rows - 1 {
+        ray_to(row, 0)?;
+        ray_to(row, cols - 1)?;
+    }
+    Ok(view)
+}
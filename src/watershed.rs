@@ -0,0 +1,163 @@
+use super::Coord;
+use crate::{
+    Error,
+    flow::downstream,
+    grid::{Grid, Sample},
+};
+
+/// Mask value of cells inside the watershed.
+pub const INSIDE: u8 = 1;
+/// Marks cells known to drain elsewhere while the mask is built.
+const OUTSIDE: u8 = 2;
+
+/// Result of [`watershed`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Watershed {
+    /// Position of the outlet cell.
+    pub outlet: Coord,
+    /// Number of cells in the watershed, the outlet included.
+    pub cells: usize,
+    /// Area of the watershed in square meters.
+    pub area: f64,
+}
+
+/// Moves a pour point onto the cell with the highest flow accumulation
+/// within `radius` meters, so an outlet placed next to a stream snaps onto it.
+///
+/// Returns the (row, column) of that cell, the nearest one if several cells
+/// have the same accumulation, or `None` if the coordinate lies outside the
+/// grid. `accumulation` is the output of [`crate::flow::flow_accumulation`].
+///
+/// # Errors
+///
+/// * `Error::BufferTooSmall` - `accumulation` holds less than `rows * cols` cells.
+pub fn snap_pour_point<T: Sample>(
+    grid: &Grid<T>,
+    accumulation: &[u32],
+    coord: impl Into<Coord>,
+    radius: f64,
+) -> Result<Option<(usize, usize)>, Error> {
+    if accumulation.len() < grid.len() {
+        return Err(Error::BufferTooSmall);
+    }
+    let Some((row, col)) = grid.index_of(coord) else {
+        return Ok(None);
+    };
+    let (dx, dy) = grid.cell_size(row);
+    let (half_rows, half_cols) = ((radius / dy) as usize, (radius / dx) as usize);
+    let mut best = (row, col);
+    let mut best_key = (accumulation[row * grid.cols + col], 0.0);
+    for r in row.saturating_sub(half_rows)..(row + half_rows + 1).min(grid.rows) {
+        for c in col.saturating_sub(half_cols)..(col + half_cols + 1).min(grid.cols) {
+            let (north, east) = ((r as f64 - row as f64) * dy, (c as f64 - col as f64) * dx);
+            let distance = libm::sqrt(north * north + east * east);
+            let value = accumulation[r * grid.cols + c];
+            if distance <= radius
+                && (value > best_key.0 || value == best_key.0 && distance < best_key.1)
+            {
+                (best, best_key) = ((r, c), (value, distance));
+            }
+        }
+    }
+    Ok(Some(best))
+}
+
+/// Delineates the watershed upstream of an outlet cell.
+///
+/// `mask` receives [`INSIDE`] for every cell draining through the outlet and
+/// 0 for all others; it must hold `rows * cols` cells. `directions` is the
+/// output of [`crate::flow::flow_direction`] for the grid, which should have
+/// been filled with [`crate::fill::fill_depressions`] first. The outlet is
+/// usually found with [`snap_pour_point`]. Parts of the watershed beyond the
+/// grid are missing, so the grid must cover the whole catchment.
+///
+/// # Errors
+///
+/// * `Error::IndexOutOfBounds` - the outlet lies outside the grid.
+/// * `Error::BufferTooSmall` - `directions` or `mask` is too small.
+/// * `Error::InvalidData` - the outlet cell is void.
+pub fn watershed<T: Sample>(
+    grid: &Grid<T>,
+    directions: &[u8],
+    outlet: (usize, usize),
+    mask: &mut [u8],
+) -> Result<Watershed, Error> {
+    let (rows, cols, len) = (grid.rows, grid.cols, grid.len());
+    if outlet.0 >= rows || outlet.1 >= cols {
+        return Err(Error::IndexOutOfBounds);
+    }
+    if directions.len() < len || mask.len() < len {
+        return Err(Error::BufferTooSmall);
+    }
+    if grid.get(outlet.0, outlet.1).is_none() {
+        return Err(Error::InvalidData);
+    }
+    let mask = &mut mask[..len];
+    mask.fill(0);
+    mask[outlet.0 * cols + outlet.1] = INSIDE;
+
+    // follow the flow from every open cell until it reaches a known cell or
+    // leaves the grid, then mark the whole path with the result
+    for start in 0..len {
+        if mask[start] != 0 {
+            continue;
+        }
+        let (mut index, mut steps) = (start, 0);
+        let state = loop {
+            match downstream(directions, rows, cols, index) {
+                Some(next) if mask[next] != 0 => break mask[next],
+                // the step limit guards against cycles in foreign direction rasters
+                Some(next) if steps < len => (index, steps) = (next, steps + 1),
+                _ => break OUTSIDE,
+            }
+        };
+        index = start;
+        while mask[index] == 0 {
+            mask[index] = state;
+            match downstream(directions, rows, cols, index) {
+                Some(next) => index = next,
+                None => break,
+            }
+        }
+    }
+
+    let mut result = Watershed {
+        outlet: grid.coord(outlet.0, outlet.1),
+        cells: 0,
+        area: 0.0,
+    };
+    for row in 0..rows {
+        let (dx, dy) = grid.cell_size(row);
+        for value in &mut mask[row * cols..(row + 1) * cols] {
+            if *value == INSIDE {
+                result.cells += 1;
+                result.area += dx * dy;
+            } else {
+                *value = 0;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Traces the outline of a watershed mask written by [`watershed`].
+///
+/// The outline runs through the middle between inside and outside cells.
+/// The lines are sorted by their number of points, so the outer boundary
+/// comes before any holes. Where the watershed touches the border of the
+/// grid the outline is open.
+#[cfg(feature = "alloc")]
+pub fn outline<T: Sample>(
+    grid: &Grid<T>,
+    mask: &[u8],
+) -> Result<alloc::vec::Vec<crate::contours::Contour>, Error> {
+    let values: alloc::vec::Vec<f32> = mask
+        .iter()
+        .take(grid.len())
+        .map(|value| f32::from(*value == INSIDE))
+        .collect();
+    let mask = Grid::new(&values, grid.rows, grid.cols, grid.origin, grid.resolution)?;
+    let mut lines = crate::contours::contour_level(&mask, 0.5);
+    lines.sort_by_key(|line| core::cmp::Reverse(line.points.len()));
+    Ok(lines)
+}
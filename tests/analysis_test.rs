@@ -0,0 +1,1416 @@
+#[cfg(test)]
+mod tests {
+
+    use srtm_embedded::bitmap;
+    use srtm_embedded::clearance::{self, Corridor};
+    use srtm_embedded::fill;
+    use srtm_embedded::flow;
+    use srtm_embedded::hillshade::{self, HillshadeOptions};
+    use srtm_embedded::horizon::{self, HorizonOptions};
+    use srtm_embedded::irradiance::{self, IrradianceOptions};
+    use srtm_embedded::los::{self, LineOfSight, LosOptions, Position};
+    use srtm_embedded::peaks::{self, Peak};
+    use srtm_embedded::profile::{self, ProfileSample};
+    use srtm_embedded::radio;
+    use srtm_embedded::skyview::{self, SkyViewOptions};
+    use srtm_embedded::solar::{self, SolarPosition};
+    use srtm_embedded::terrain::{self, TerrainIndex};
+    use srtm_embedded::track::{self, TrackOptions};
+    use srtm_embedded::viewshed::{self, ViewshedOptions};
+    use srtm_embedded::watershed;
+    use srtm_embedded::{Bounds, Coord, Grid, MemoryReader, Mosaic, Resolution, Tile};
+
+    const STEP: f64 = 1.0 / 1200.0;
+
+    /// Builds the content of an SRTM3 file for the tile at `origin`,
+    /// with heights given by `height(lat, lon)`.
+    fn synthetic_tile(origin: (i8, i16), height: impl Fn(f64, f64) -> i16) -> &'static [u8] {
+        let points = Resolution::SRTM3.point_per_degree();
+        let mut data = Vec::with_capacity(Resolution::SRTM3.expected_file_length());
+        for row in 0..points {
+            for col in 0..points {
+                let lat = origin.0 as f64 + 1.0 - row as f64 * STEP;
+                let lon = origin.1 as f64 + col as f64 * STEP;
+                data.extend_from_slice(&height(lat, lon).to_be_bytes());
+            }
+        }
+        Box::leak(data.into_boxed_slice())
+    }
+
+    /// Returns an SRTM3 tile reading the synthetic N49E008 tile.
+    fn synthetic_source(height: impl Fn(f64, f64) -> i16) -> Tile<MemoryReader<'static, 1>> {
+        let mut reader = MemoryReader::<1>::new();
+        reader.add_tile(49, 8, synthetic_tile((49, 8), height)).ok();
+        Tile::<MemoryReader<1>>::new(Resolution::SRTM3, reader)
+    }
+
+    /// One meter per sample towards the east.
+    fn ramp(_lat: f64, lon: f64) -> i16 {
+        (100.0 + (lon - 8.0) * 1200.0).round() as i16
+    }
+
+    /// Valley along 8.5° E sloping 1 m per sample to the south,
+    /// with sides rising 2 m per sample.
+    fn valley(lat: f64, lon: f64) -> i16 {
+        (1000.0 + ((lon - 8.5).abs() * 1200.0).round() * 2.0 + ((lat - 49.0) * 1200.0).round())
+            as i16
+    }
+
+    #[test]
+    fn test_profile_between() {
+        let mut source = synthetic_source(ramp);
+        let (start, end) = (Coord::new(49.5, 8.1), Coord::new(49.5, 8.2));
+        let mut samples = [ProfileSample::default(); 128];
+        let (count, summary) =
+            profile::profile_between(&mut source, start, end, 100.0, &mut samples).unwrap();
+
+        let length = start.distance_to(end);
+        assert_eq!(count, profile::sample_count(&[start, end], 100.0));
+        assert_eq!(count, (length / 100.0) as usize + 2);
+        assert!((summary.length - length).abs() < 1e-6);
+        assert_eq!(samples[0].height, Some(220));
+        assert_eq!(samples[count - 1].height, Some(340));
+        assert_eq!(samples[count - 1].coord, end);
+        assert_eq!(summary.min, Some(220));
+        assert_eq!(summary.max, Some(340));
+        assert_eq!(summary.total_ascent, 120);
+        assert_eq!(summary.total_descent, 0);
+        assert!(
+            samples[..count]
+                .windows(2)
+                .all(|pair| (pair[1].distance - pair[0].distance) <= 100.0 + 1e-9)
+        );
+    }
+
+    #[test]
+    fn test_profile_polyline_with_voids() {
+        let mut source = synthetic_source(|lat, lon| {
+            if lon > 8.14 && lon < 8.16 {
+                srtm_embedded::tiles::VOID_VALUE
+            } else {
+                ramp(lat, lon)
+            }
+        });
+        let path = [
+            Coord::new(49.5, 8.1),
+            Coord::new(49.5, 8.2),
+            Coord::new(49.6, 8.1),
+        ];
+        let mut samples = [ProfileSample::default(); 512];
+        let (count, summary) = profile::profile(&mut source, &path, 50.0, &mut samples).unwrap();
+        assert!(
+            samples[..count]
+                .iter()
+                .any(|sample| sample.height.is_none())
+        );
+        assert_eq!(samples[count - 1].height, Some(220));
+        assert_eq!(summary.max, Some(340));
+        assert_eq!(summary.total_ascent, summary.total_descent);
+        assert_eq!(summary.total_ascent, 120);
+    }
+
+    #[test]
+    fn test_track_stats() {
+        let mut source = synthetic_source(ramp);
+        let options = TrackOptions::default();
+        // 120 m up to the east and 60 m back down
+        let track = [
+            Coord::new(49.5, 8.1),
+            Coord::new(49.5, 8.2),
+            Coord::new(49.5, 8.15),
+        ];
+        let stats = track::track_stats(&mut source, &track, &options).unwrap();
+        let (length, back) = (
+            track[0].distance_to(track[1]),
+            track[1].distance_to(track[2]),
+        );
+        assert!((stats.distance - length - back).abs() < 1e-6);
+        let surface = (length * length + 120.0 * 120.0).sqrt() + (back * back + 60.0 * 60.0).sqrt();
+        assert!((stats.surface_distance - surface).abs() < 1e-6);
+        assert!((stats.total_ascent - 120.0).abs() < 1e-6);
+        assert!((stats.total_descent - 60.0).abs() < 1e-6);
+        let grade = 120.0 / length * 100.0;
+        assert!((stats.max_grade.unwrap() - grade).abs() < 1e-6);
+        assert!((stats.min_grade.unwrap() + grade).abs() < 1e-6);
+
+        // zigzag of 2 m below the hysteresis, then a 10 m climb
+        let mut zigzag: Vec<Coord> = (0..20)
+            .map(|i| Coord::new(49.5, 8.1 + (i % 3) as f64 * STEP))
+            .collect();
+        zigzag.push(Coord::new(49.5, 8.1 + 12.0 * STEP));
+        let stats = track::track_stats(&mut source, &zigzag, &options).unwrap();
+        assert!((stats.total_ascent - 12.0).abs() < 1e-6);
+        assert_eq!(stats.total_descent, 0.0);
+        let options = TrackOptions {
+            hysteresis: 0.0,
+            ..options
+        };
+        let stats = track::track_stats(&mut source, &zigzag, &options).unwrap();
+        assert!((stats.total_ascent - 24.0).abs() < 1e-6);
+        assert!((stats.total_descent - 12.0).abs() < 1e-6);
+
+        assert_eq!(
+            track::track_stats(&mut source, &[], &options),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+        let single = track::track_stats(&mut source, &track[..1], &options).unwrap();
+        assert_eq!(single.distance, 0.0);
+        assert_eq!(single.max_grade, None);
+    }
+
+    #[test]
+    fn test_resample() {
+        use srtm_embedded::resample::{self, ResampleMethod};
+        use srtm_embedded::tiles::VOID_VALUE;
+
+        // ramp with a void corner in the north-west up to column 120
+        let mut source = synthetic_source(|lat, lon| {
+            if lat > 49.9 && lon < 8.1 + STEP / 2.0 {
+                VOID_VALUE
+            } else {
+                ramp(lat, lon)
+            }
+        });
+        let bounds = Bounds::new((49.0, 8.0), (50.0, 9.0));
+        let mut out = vec![0i16; 401 * 401];
+        let at = |out: &[i16], row: usize, col: usize| out[row * 401 + col];
+        for method in [
+            ResampleMethod::Nearest,
+            ResampleMethod::Bilinear,
+            ResampleMethod::Average,
+        ] {
+            resample::resample(&mut source, bounds, 401, 401, method, &mut out).unwrap();
+            assert_eq!(at(&out, 200, 100), 400);
+            assert_eq!(at(&out, 400, 1), 103);
+            assert_eq!(at(&out, 10, 39), VOID_VALUE);
+            assert_eq!(at(&out, 10, 41), 223);
+        }
+        // only the average sees the valid column next to the void edge
+        assert_eq!(at(&out, 10, 40), 221);
+        resample::resample(
+            &mut source,
+            bounds,
+            401,
+            401,
+            ResampleMethod::Nearest,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(at(&out, 10, 40), VOID_VALUE);
+
+        let heights = [0i16, 10, 20, 0, 10, 20, 0, 10, 20];
+        let grid = Grid::new(&heights, 3, 3, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let mut fine = [0i16; 25];
+        resample::resample_grid(&grid, 5, 5, ResampleMethod::Bilinear, &mut fine).unwrap();
+        assert_eq!(fine[5..10], [0, 5, 10, 15, 20]);
+        let mut coarse = [0i16; 4];
+        resample::resample_grid(&grid, 2, 2, ResampleMethod::Average, &mut coarse).unwrap();
+        assert_eq!(coarse, [5, 15, 5, 15]);
+
+        assert_eq!(
+            resample::resample_grid(&grid, 1, 2, ResampleMethod::Nearest, &mut coarse),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+        assert_eq!(
+            resample::resample_grid(&grid, 3, 3, ResampleMethod::Nearest, &mut coarse),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+        assert_eq!(
+            resample::resample_grid(&grid, usize::MAX, 2, ResampleMethod::Nearest, &mut coarse),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_profile_errors() {
+        let mut source = synthetic_source(ramp);
+        let path = [Coord::new(49.5, 8.1), Coord::new(49.5, 8.2)];
+        let mut samples = [ProfileSample::default(); 4];
+        assert_eq!(
+            profile::profile(&mut source, &path, 100.0, &mut samples),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+        assert_eq!(
+            profile::profile(&mut source, &path, 0.0, &mut samples),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+        assert_eq!(
+            profile::profile(&mut source, &[], 10.0, &mut samples),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_profile_vec() {
+        let mut source = synthetic_source(ramp);
+        let path = [Coord::new(49.5, 8.1), Coord::new(49.5, 8.2)];
+        let (samples, summary) = profile::profile_vec(&mut source, &path, 100.0).unwrap();
+        assert_eq!(samples.len(), profile::sample_count(&path, 100.0));
+        assert_eq!(summary.total_ascent, 120);
+    }
+
+    #[test]
+    fn test_slope_aspect() {
+        let mut source = synthetic_source(ramp);
+        let value = terrain::slope_aspect(&mut source, (49.5, 8.5)).unwrap();
+        let (dx, _) = Resolution::SRTM3.cell_size(49.5);
+        assert!((value.slope - (1.0 / dx).atan().to_degrees()).abs() < 1e-3);
+        assert!((value.slope_percent() - 100.0 / dx).abs() < 1e-3);
+        assert!(
+            (value.aspect.unwrap() - 270.0).abs() < 1e-9,
+            "ramp faces west"
+        );
+
+        // rising towards north by 2 m per sample
+        let mut source = synthetic_source(|lat, _| ((lat - 49.0) * 2400.0).round() as i16);
+        let value = terrain::slope_aspect(&mut source, (49.5, 8.5)).unwrap();
+        let (_, dy) = Resolution::SRTM3.cell_size(49.5);
+        assert!((value.slope - (2.0 / dy).atan().to_degrees()).abs() < 1e-3);
+        assert!((value.aspect.unwrap() - 180.0).abs() < 1e-9, "faces south");
+
+        let mut source = synthetic_source(|_, _| 50);
+        let value = terrain::slope_aspect(&mut source, (49.5, 8.5)).unwrap();
+        assert_eq!(value.slope, 0.0);
+        assert_eq!(value.aspect, None);
+    }
+
+    #[test]
+    fn test_slope_aspect_grid() {
+        let mut source = synthetic_source(ramp);
+        let mut buffer = [0i16; 5 * 6];
+        let grid = Grid::read(&mut source, (49.5, 8.5), 5, 6, &mut buffer).unwrap();
+        assert_eq!(grid.get(0, 0), Some(700.0));
+        let mut slope = [0f32; 30];
+        let mut aspect = [0f32; 30];
+        terrain::slope_aspect_grid(&grid, &mut slope, &mut aspect).unwrap();
+        assert!(
+            slope[0].is_nan() && aspect[29].is_nan(),
+            "border cells are undefined"
+        );
+        let expected = terrain::slope_aspect(&mut source, grid.coord(2, 3)).unwrap();
+        assert!((slope[2 * 6 + 3] as f64 - expected.slope).abs() < 1e-4);
+        assert_eq!(aspect[2 * 6 + 3], 270.0);
+        assert_eq!(
+            terrain::slope_aspect_grid(&grid, &mut [0f32; 29], &mut aspect),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+        // rows * cols overflows and must not pass the size check
+        assert_eq!(
+            Grid::new(
+                &[0i16; 4],
+                usize::MAX / 2 + 1,
+                2,
+                (49.5, 8.5),
+                Resolution::SRTM3
+            ),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_terrain_indices() {
+        let mut source = synthetic_source(ramp);
+        let indices = terrain::terrain_indices(&mut source, (49.5, 8.5)).unwrap();
+        assert!((indices.tri - 6f64.sqrt()).abs() < 1e-9);
+        assert_eq!((indices.tpi, indices.roughness), (0.0, 2.0));
+        assert_eq!(
+            (indices.profile_curvature, indices.plan_curvature),
+            (0.0, 0.0)
+        );
+
+        // valley running north, rising 5 m per row towards the north
+        let valley: Vec<i16> = (0..7 * 7)
+            .map(|i| 100 + ((i % 7) as i16 - 3).pow(2) * 10 + (6 - (i / 7) as i16) * 5)
+            .collect();
+        let grid = Grid::new(&valley, 7, 7, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let bottom = terrain::terrain_indices_at(&grid, 3, 3).unwrap();
+        assert_eq!(bottom.tpi, -10.0 * 6.0 / 8.0);
+        assert!(bottom.plan_curvature > 0.0, "flow converges in the valley");
+        let side = terrain::terrain_indices_at(&grid, 3, 5).unwrap();
+        assert!(side.plan_curvature > 0.0);
+        assert!(side.profile_curvature > 0.0, "the side flattens downhill");
+        assert_eq!(terrain::terrain_indices_at(&grid, 0, 3), None);
+
+        // the inverted valley is a ridge
+        let ridge: Vec<i16> = valley.iter().map(|z| 1000 - z).collect();
+        let grid = Grid::new(&ridge, 7, 7, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let top = terrain::terrain_indices_at(&grid, 3, 3).unwrap();
+        assert!(top.tpi > 0.0 && top.plan_curvature < 0.0);
+        assert!(
+            terrain::terrain_indices_at(&grid, 3, 5)
+                .unwrap()
+                .profile_curvature
+                < 0.0
+        );
+
+        let mut out = [0f32; 7 * 7];
+        terrain::terrain_index_grid(&grid, TerrainIndex::Roughness, &mut out).unwrap();
+        assert!(out[0].is_nan());
+        assert_eq!(out[3 * 7 + 3] as f64, top.roughness);
+        assert_eq!(
+            terrain::terrain_index_grid(&grid, TerrainIndex::Tri, &mut out[..48]),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_hillshade() {
+        let bounds = Bounds::new((49.4, 8.4), (49.5, 8.5));
+        let mut out = [0u8; 8 * 6];
+
+        let mut flat = synthetic_source(|_, _| 200);
+        let options = HillshadeOptions::default();
+        hillshade::hillshade(&mut flat, bounds, 8, 6, &options, &mut out).unwrap();
+        assert!(
+            out.iter().all(|value| *value == 180),
+            "flat is lit by sin(45°)"
+        );
+        let options = HillshadeOptions {
+            multidirectional: true,
+            ..Default::default()
+        };
+        hillshade::hillshade(&mut flat, bounds, 8, 6, &options, &mut out).unwrap();
+        assert!(out.iter().all(|value| *value == 180));
+
+        // steep slope facing west
+        let mut source = synthetic_source(|_, lon| ((lon - 8.0) * 24000.0).round() as i16);
+        let mut from_west = [0u8; 8 * 6];
+        let options = HillshadeOptions {
+            azimuth: 270.0,
+            ..Default::default()
+        };
+        hillshade::hillshade(&mut source, bounds, 8, 6, &options, &mut from_west).unwrap();
+        let options = HillshadeOptions {
+            azimuth: 90.0,
+            ..Default::default()
+        };
+        hillshade::hillshade(&mut source, bounds, 8, 6, &options, &mut out).unwrap();
+        assert!(
+            from_west
+                .iter()
+                .zip(out.iter())
+                .all(|(west, east)| west > east)
+        );
+
+        assert_eq!(
+            hillshade::hillshade(&mut source, bounds, 8, 7, &options, &mut out),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+        assert_eq!(
+            hillshade::hillshade(&mut source, bounds, usize::MAX, 2, &options, &mut out),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    /// Flat terrain at 100 m with a 300 m high north-south wall at 8.5° E.
+    fn wall(_lat: f64, lon: f64) -> i16 {
+        if (lon - 8.5).abs() < 0.002 { 300 } else { 100 }
+    }
+
+    #[test]
+    fn test_line_of_sight_blocked() {
+        let mut source = synthetic_source(wall);
+        let options = LosOptions::default();
+        let from = Position::above_ground((49.5, 8.4), 10.0);
+        let to = Position::above_ground((49.5, 8.6), 10.0);
+        let result = los::line_of_sight(&mut source, from, to, &options).unwrap();
+        let LineOfSight::Blocked(obstruction) = result else {
+            panic!("the wall blocks the view");
+        };
+        assert!(!result.is_visible());
+        assert!((obstruction.coord.lon - 8.5).abs() < 0.003);
+        assert!(obstruction.terrain_height > obstruction.line_height);
+        assert!((obstruction.distance - from.coord.distance_to(obstruction.coord)).abs() < 1.0);
+
+        let from = Position::above_sea_level((49.5, 8.4), 400.0);
+        let to = Position::above_sea_level((49.5, 8.6), 400.0);
+        let result = los::line_of_sight(&mut source, from, to, &options).unwrap();
+        assert_eq!(result, LineOfSight::Visible);
+    }
+
+    #[test]
+    fn test_fresnel_clearance() {
+        let mut source = synthetic_source(wall);
+        let options = LosOptions::default();
+        let (west, east) = (Coord::new(49.5, 8.4), Coord::new(49.5, 8.6));
+        let length = west.distance_to(east);
+        // 2.4 GHz: 21 m radius and 3 m earth bulge over the 300 m wall in the middle
+        let radius = radio::fresnel_radius(length / 2.0, length / 2.0, 2.4e9);
+        assert!((radius - (0.1249 * length / 4.0).sqrt()).abs() < 0.01);
+        let bulge = los::earth_bulge(length / 2.0, length / 2.0, los::STANDARD_K_FACTOR);
+        let link = |height: f64| {
+            let from = Position::above_sea_level(west, height);
+            let to = Position::above_sea_level(east, height);
+            radio::fresnel_clearance(&mut synthetic_source(wall), from, to, 2.4e9, &options)
+                .unwrap()
+        };
+
+        let clear = link(320.0);
+        let worst = clear.worst.unwrap();
+        assert!((worst.coord.lon - 8.5).abs() < 0.003);
+        assert!((worst.radius / radius - 1.0).abs() < 0.01);
+        let expected = (320.0 - 300.0 - bulge) / radius * 100.0;
+        assert!((clear.clearance().unwrap() - expected).abs() < 1.0);
+        assert!(clear.is_clear(60.0) && clear.line_of_sight());
+        assert!((clear.length - length).abs() < 1e-6);
+
+        let grazing = link(310.0);
+        assert!(!grazing.is_clear(60.0) && grazing.line_of_sight());
+        let blocked = link(300.0);
+        assert!(!blocked.line_of_sight());
+        assert!(blocked.clearance().unwrap() < 0.0);
+
+        let from = Position::above_ground(west, 10.0);
+        let to = Position::above_ground(east, 10.0);
+        assert_eq!(
+            radio::fresnel_clearance(&mut source, from, to, 0.0, &options),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+    }
+
+    #[test]
+    fn test_path_loss_and_coverage() {
+        let mut source = synthetic_source(wall);
+        let options = radio::PropagationOptions::default();
+        let mut profile = [0.0; 64];
+        let (west, east) = (Coord::new(49.5, 8.4), Coord::new(49.5, 8.6));
+        let length = west.distance_to(east);
+        let free = radio::free_space_loss(length, options.frequency);
+        // 868 MHz over 14.5 km: 32.4 + 20 log10(14.5) + 20 log10(868) dB
+        assert!((free - 114.4).abs() < 0.1);
+        assert!(radio::knife_edge_loss(-1.0) == 0.0);
+        assert!((radio::knife_edge_loss(0.0) - 6.0).abs() < 0.1);
+
+        // high above the wall only the free space loss remains
+        let from = Position::above_sea_level(west, 500.0);
+        let to = Position::above_sea_level(east, 500.0);
+        let loss = radio::path_loss(&mut source, from, to, &options, &mut profile).unwrap();
+        assert!((loss - free).abs() < 1e-6);
+        // behind the wall diffraction adds a lot
+        let from = Position::above_ground(west, 10.0);
+        let to = Position::above_ground(east, 10.0);
+        let loss = radio::path_loss(&mut source, from, to, &options, &mut profile).unwrap();
+        assert!(loss > free + 20.0);
+        assert_eq!(
+            radio::path_loss(&mut source, from, to, &options, &mut profile[..2]),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+
+        let transmitter = Position::above_ground((49.5, 8.48), 30.0);
+        let radius = 2500.0;
+        let (rows, cols) = viewshed::dimensions(Resolution::SRTM3, 49.5, radius);
+        let mut out = vec![0.0f32; rows * cols];
+        let coverage = radio::coverage(
+            &mut source,
+            transmitter,
+            1.5,
+            radius,
+            &options,
+            &mut out,
+            &mut profile,
+        )
+        .unwrap();
+        assert_eq!((coverage.rows, coverage.cols), (rows, cols));
+        let (row, col) = (rows / 2, cols / 2);
+        assert!(out[row * cols + col].is_nan());
+        assert!(out[0].is_nan());
+        // same distance to the west in the open and to the east behind the wall
+        let open = out[row * cols + col - 36];
+        let shadowed = out[row * cols + col + 36];
+        let distance = Coord::new(49.5, 8.48).distance_to(Coord::new(49.5, 8.51));
+        // the low receiver still loses a little to the ground in its Fresnel zone
+        let free = radio::free_space_loss(distance, options.frequency) as f32;
+        assert!(open >= free && open < free + 6.0);
+        assert!(shadowed > open + 30.0);
+        // a radius whose cell count overflows
+        assert_eq!(
+            radio::coverage(
+                &mut source,
+                transmitter,
+                1.5,
+                f64::INFINITY,
+                &options,
+                &mut out,
+                &mut profile,
+            ),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_horizon() {
+        let mut source = synthetic_source(wall);
+        let observer = Position::above_ground((49.5, 8.45), 0.0);
+        let options = HorizonOptions {
+            max_distance: 5_000.0,
+            ..Default::default()
+        };
+        let east = horizon::horizon_elevation(&mut source, observer, 90.0, &options)
+            .unwrap()
+            .unwrap();
+        let distance = observer.coord.distance_to(Coord::new(49.5, 8.498));
+        let expected = (200.0 / distance).atan().to_degrees();
+        assert!(
+            (east - expected).abs() < 0.3,
+            "{east}° instead of {expected}°"
+        );
+
+        let mut bins = [0f32; 8];
+        horizon::horizon(&mut source, observer, &options, &mut bins).unwrap();
+        assert_eq!(bins[2], east as f32);
+        assert!(
+            bins[6] < 0.0 && bins[6] > -0.01,
+            "flat land drops below the eye"
+        );
+        assert!(bins.iter().all(|angle| *angle <= bins[2]));
+
+        let flat = HorizonOptions {
+            earth_curvature: false,
+            ..options
+        };
+        horizon::horizon(&mut source, observer, &flat, &mut bins).unwrap();
+        assert!(bins[6].abs() < 1e-6);
+        assert_eq!(
+            horizon::horizon(&mut source, observer, &options, &mut []),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+        for invalid in [
+            HorizonOptions {
+                max_distance: f64::INFINITY,
+                ..options
+            },
+            HorizonOptions {
+                max_distance: f64::NAN,
+                ..options
+            },
+            HorizonOptions {
+                spacing: Some(f64::NAN),
+                ..options
+            },
+            HorizonOptions {
+                spacing: Some(f64::INFINITY),
+                ..options
+            },
+        ] {
+            assert_eq!(
+                horizon::horizon_elevation(&mut source, observer, 90.0, &invalid),
+                Err(srtm_embedded::Error::InvalidParameter)
+            );
+        }
+    }
+
+    #[test]
+    fn test_solar_position() {
+        // 2024-06-21, solar noon in Heidelberg at 11:27:42 UTC
+        let noon = 1_718_928_000 + 11 * 3600 + 27 * 60 + 42;
+        let sun = solar::solar_position(noon, (49.5, 8.5));
+        assert!((sun.azimuth - 180.0).abs() < 0.5, "azimuth {}", sun.azimuth);
+        assert!((sun.elevation - (90.0 - 49.5 + 23.44)).abs() < 0.1);
+        // the sun rises in the north-east and is down at midnight
+        let morning = solar::solar_position(noon - 7 * 3600, (49.5, 8.5));
+        assert!(morning.is_up() && morning.azimuth > 45.0 && morning.azimuth < 90.0);
+        assert!(!solar::solar_position(noon + 12 * 3600, (49.5, 8.5)).is_up());
+        // near the zenith at the tropic of cancer
+        let tropic = solar::solar_position(1_718_928_000 + 12 * 3600, (23.44, 0.0));
+        assert!(tropic.elevation > 89.0);
+    }
+
+    #[test]
+    fn test_shadow_mask() {
+        let mut source = synthetic_source(wall);
+        let mut heights = [0i16; 3 * 60];
+        let grid = Grid::read(&mut source, (49.5, 8.5 - 50.0 * STEP), 3, 60, &mut heights).unwrap();
+        let mut bitmap = [0u8; 23];
+        // the 200 m wall in the east casts a shadow of 200 / tan(10°) = 1134 m
+        let sun = SolarPosition {
+            azimuth: 90.0,
+            elevation: 10.0,
+        };
+        let shadowed = solar::shadow_mask(&grid, &sun, &mut bitmap).unwrap();
+        let (dx, _) = grid.cell_size(1);
+        let shade = |bitmap: &[u8], col: usize| bitmap::get_bit(bitmap, 60 + col);
+        assert!(shade(&bitmap, 46) && shade(&bitmap, 47));
+        assert!(
+            !shade(&bitmap, 48) && !shade(&bitmap, 53),
+            "the wall top is lit"
+        );
+        let length = (0..48).rev().take_while(|col| shade(&bitmap, *col)).count();
+        assert!(
+            (length as f64 * dx - 1134.0).abs() < 2.0 * dx,
+            "{length} cells"
+        );
+        assert_eq!(shadowed, 3 * length);
+
+        // from the west the wall shades its eastern side, at night everything is dark
+        let sun = SolarPosition {
+            azimuth: 270.0,
+            elevation: 10.0,
+        };
+        solar::shadow_mask(&grid, &sun, &mut bitmap).unwrap();
+        assert!(!shade(&bitmap, 40) && shade(&bitmap, 53));
+        let night = SolarPosition {
+            azimuth: 0.0,
+            elevation: -5.0,
+        };
+        assert_eq!(solar::shadow_mask(&grid, &night, &mut bitmap), Ok(180));
+        assert_eq!(
+            solar::shadow_mask(&grid, &sun, &mut bitmap[..22]),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_sky_view() {
+        let mut source = synthetic_source(wall);
+        let options = SkyViewOptions::default();
+        let open = Position::above_ground((49.5, 8.3), 0.0);
+        assert_eq!(skyview::sky_view(&mut source, open, &options), Ok(1.0));
+        let below_wall = Position::above_ground((49.5, 8.497), 0.0);
+        let factor = skyview::sky_view(&mut source, below_wall, &options).unwrap();
+        assert!(factor > 0.5 && factor < 0.95, "{factor}");
+        let more = SkyViewOptions {
+            directions: 64,
+            ..options
+        };
+        let finer = skyview::sky_view(&mut source, below_wall, &more).unwrap();
+        assert!((finer - factor).abs() < 0.05);
+        assert_eq!(
+            skyview::sky_view(
+                &mut source,
+                open,
+                &SkyViewOptions {
+                    directions: 0,
+                    ..options
+                }
+            ),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+
+        // the raster agrees with the point values inside the grid
+        let mut heights = [0i16; 21 * 41];
+        let grid =
+            Grid::read(&mut source, (49.5, 8.5 - 30.0 * STEP), 21, 41, &mut heights).unwrap();
+        let mut out = [0f32; 21 * 41];
+        skyview::sky_view_grid(&grid, &options, &mut out).unwrap();
+        assert_eq!(out[10 * 41], 1.0);
+        let cell = 10 * 41 + 26;
+        let point = Position::above_ground(grid.coord(10, 26), 0.0);
+        let expected = skyview::sky_view(&mut source, point, &options).unwrap();
+        assert!(
+            (out[cell] as f64 - expected).abs() < 0.05,
+            "{} {expected}",
+            out[cell]
+        );
+        assert!(out[cell] < 0.95);
+        assert_eq!(
+            skyview::sky_view_grid(&grid, &options, &mut out[..40]),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_irradiance() {
+        // daily energy on a 30° slope facing south, a flat cell and a 30° slope facing north
+        let rise = (30f64.to_radians().tan() * Resolution::SRTM3.cell_size(49.5).1) as i16;
+        let planes: Vec<Vec<i16>> = [-rise, 0, rise]
+            .iter()
+            .map(|rise| (0..5 * 5).map(|i| 1000 + (i / 5) as i16 * rise).collect())
+            .collect();
+        let daily = |options: &IrradianceOptions| -> Vec<f32> {
+            planes
+                .iter()
+                .map(|heights| {
+                    let grid = Grid::new(heights, 5, 5, (49.5, 8.5), Resolution::SRTM3).unwrap();
+                    let mut out = [0f32; 25];
+                    let mut sky_view = [0f32; 25];
+                    let mut shadow = [0u8; 4];
+                    irradiance::irradiance(&grid, options, &mut out, &mut sky_view, &mut shadow)
+                        .unwrap();
+                    assert!(out[0].is_nan() && out[24].is_nan());
+                    out[2 * 5 + 2]
+                })
+                .collect()
+        };
+        let summer = daily(&IrradianceOptions::default());
+        assert!(summer[1] > 6000.0 && summer[1] < 10000.0, "{summer:?}");
+        assert!(summer[0] > summer[2]);
+        let winter = daily(&IrradianceOptions {
+            start: 1_734_739_200, // 2024-12-21
+            ..Default::default()
+        });
+        assert!(
+            winter[0] > 2.0 * winter[1] && winter[1] > winter[2],
+            "{winter:?}"
+        );
+        assert!(winter[1] < summer[1] / 3.0);
+
+        // skipping days counts them with the computed ones
+        let week = IrradianceOptions {
+            days: 7,
+            ..Default::default()
+        };
+        let sampled = IrradianceOptions {
+            day_step: 7,
+            ..week
+        };
+        assert!((daily(&week)[1] / daily(&sampled)[1] - 1.0).abs() < 0.01);
+
+        // a deep trench is shadowed and sees less sky than the open plain
+        let mut trench = vec![1000i16; 9 * 9];
+        for row in 0..9 {
+            trench[row * 9 + 3] = 1200;
+            trench[row * 9 + 5] = 1200;
+            trench[row * 9 + 4] = 900;
+        }
+        let grid = Grid::new(&trench, 9, 9, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let mut out = [0f32; 81];
+        let mut sky_view = [0f32; 81];
+        let mut shadow = [0u8; 11];
+        irradiance::irradiance(&grid, &week, &mut out, &mut sky_view, &mut shadow).unwrap();
+        assert!(out[4 * 9 + 4] < out[4 * 9 + 1] / 2.0);
+        assert!(sky_view[4 * 9 + 4] < 0.5 && sky_view[4 * 9 + 1] > 0.5);
+        assert_eq!(
+            irradiance::irradiance(
+                &grid,
+                &IrradianceOptions {
+                    time_step: 7,
+                    ..week
+                },
+                &mut out,
+                &mut sky_view,
+                &mut shadow
+            ),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+    }
+
+    #[test]
+    fn test_is_sunlit() {
+        let mut source = synthetic_source(wall);
+        let options = HorizonOptions {
+            max_distance: 5_000.0,
+            ..Default::default()
+        };
+        // a morning minute with the sun low in the east, below the 3° of the wall
+        let day = 1_710_892_800; // 2024-03-20
+        let low = (day + 4 * 3600..day + 8 * 3600)
+            .step_by(60)
+            .find(|time| {
+                let sun = solar::solar_position(*time, (49.5, 8.45));
+                sun.elevation > 1.0 && sun.elevation < 2.5
+            })
+            .unwrap();
+        let west = Position::above_ground((49.5, 8.45), 0.0);
+        let east = Position::above_ground((49.5, 8.55), 0.0);
+        assert!(!solar::is_sunlit(&mut source, west, low, &options).unwrap());
+        assert!(solar::is_sunlit(&mut source, east, low, &options).unwrap());
+        assert!(solar::is_sunlit(&mut source, west, low + 2 * 3600, &options).unwrap());
+        assert!(!solar::is_sunlit(&mut source, east, day, &options).unwrap());
+        let endless = HorizonOptions {
+            max_distance: f64::INFINITY,
+            ..options
+        };
+        assert_eq!(
+            solar::is_sunlit(&mut source, west, low, &endless),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+    }
+
+    #[test]
+    fn test_line_of_sight_earth_curvature() {
+        let mut source = synthetic_source(|_, _| 0);
+        let from = Position::above_ground((49.5, 8.05), 10.0);
+        let to = Position::above_ground((49.5, 8.95), 10.0);
+        let flat = LosOptions {
+            earth_curvature: false,
+            spacing: Some(500.0),
+            ..Default::default()
+        };
+        assert!(
+            los::line_of_sight(&mut source, from, to, &flat)
+                .unwrap()
+                .is_visible()
+        );
+        let curved = LosOptions {
+            spacing: Some(500.0),
+            ..Default::default()
+        };
+        assert!(
+            !los::line_of_sight(&mut source, from, to, &curved)
+                .unwrap()
+                .is_visible()
+        );
+
+        // the bulge in the middle is about 62 m with standard refraction
+        let length = from.coord.distance_to(to.coord);
+        let bulge = los::earth_bulge(length / 2.0, length / 2.0, los::STANDARD_K_FACTOR);
+        let from = Position::above_ground((49.5, 8.05), bulge + 1.0);
+        let to = Position::above_ground((49.5, 8.95), bulge + 1.0);
+        assert!(
+            los::line_of_sight(&mut source, from, to, &curved)
+                .unwrap()
+                .is_visible()
+        );
+    }
+
+    #[test]
+    fn test_viewshed() {
+        let options = ViewshedOptions::default();
+        let (rows, cols) = viewshed::dimensions(Resolution::SRTM3, 49.5, 2000.0);
+        assert_eq!((rows, cols), (43, 67));
+        let mut bitmap = vec![0u8; bitmap::bitmap_len(rows, cols)];
+
+        let mut flat = synthetic_source(|_, _| 100);
+        let view =
+            viewshed::viewshed(&mut flat, (49.5, 8.49), 2000.0, &options, &mut bitmap).unwrap();
+        assert_eq!((view.rows, view.cols), (rows, cols));
+        assert!(view.is_visible(&bitmap, rows / 2, cols / 2));
+        assert!(
+            view.is_visible(&bitmap, rows / 2, 0),
+            "2 km west is inside the radius"
+        );
+        assert!(
+            !view.is_visible(&bitmap, 0, 0),
+            "the corner is outside the radius"
+        );
+        assert_eq!(
+            view.visible,
+            bitmap
+                .iter()
+                .map(|byte| byte.count_ones() as usize)
+                .sum::<usize>()
+        );
+
+        let mut source = synthetic_source(wall);
+        let view =
+            viewshed::viewshed(&mut source, (49.5, 8.49), 2000.0, &options, &mut bitmap).unwrap();
+        let col_of = |lon: f64| ((lon - view.origin.lon) * 1200.0).round() as usize;
+        assert!(view.is_visible(&bitmap, rows / 2, col_of(8.48)));
+        assert!(
+            view.is_visible(&bitmap, rows / 2, col_of(8.5 - 2.0 / 1200.0)),
+            "the face of the wall"
+        );
+        assert!(
+            !view.is_visible(&bitmap, rows / 2, col_of(8.51)),
+            "behind the wall"
+        );
+
+        assert_eq!(
+            viewshed::viewshed(
+                &mut source,
+                (49.5, 8.49),
+                2000.0,
+                &options,
+                &mut bitmap[..10]
+            ),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_corridor_max() {
+        let mut source = synthetic_source(wall);
+        let position = Coord::new(49.5, 8.45);
+        let mut corridor = Corridor {
+            position,
+            track: 90.0,
+            speed: 50.0,
+            lookahead: 60.0,
+            half_width: 200.0,
+        };
+        let result = clearance::corridor_max(&mut source, &corridor, 1000).unwrap();
+        assert_eq!(result.height, 100, "the wall is 3.6 km ahead");
+        assert!(result.samples <= 1000);
+
+        corridor.lookahead = 80.0;
+        let result = clearance::corridor_max(&mut source, &corridor, 1000).unwrap();
+        assert_eq!(result.height, 300);
+        assert!((result.coord.lon - 8.5).abs() < 0.003);
+        assert!((result.along - position.distance_to((49.5, 8.498))).abs() < 100.0);
+        assert_eq!(result.clearance(500.0), 200.0);
+
+        // flying north next to the wall, inside the lateral buffer
+        let corridor = Corridor {
+            position: Coord::new(49.5, 8.495),
+            track: 0.0,
+            speed: 30.0,
+            lookahead: 10.0,
+            half_width: 400.0,
+        };
+        // 4 x 15 cells of 93 x 60 m overlap the 300 x 800 m corridor
+        let needed = clearance::sample_count(&corridor, Resolution::SRTM3);
+        assert_eq!(needed, 60);
+        let result = clearance::corridor_max(&mut source, &corridor, needed).unwrap();
+        assert_eq!(result.height, 300);
+        assert!(result.across > 0.0, "the wall is on the right");
+        assert_eq!(result.samples, needed);
+        // a smaller budget would have to skip terrain
+        for max_samples in [needed - 1, 1, 0] {
+            assert_eq!(
+                clearance::corridor_max(&mut source, &corridor, max_samples),
+                Err(srtm_embedded::Error::InvalidParameter)
+            );
+        }
+
+        // a standing vehicle reads the cell it is in
+        let corridor = Corridor {
+            speed: 0.0,
+            half_width: 0.0,
+            ..corridor
+        };
+        assert_eq!(clearance::sample_count(&corridor, Resolution::SRTM3), 1);
+        let result = clearance::corridor_max(&mut source, &corridor, 1).unwrap();
+        assert_eq!(result.height, 100);
+    }
+
+    #[test]
+    fn test_corridor_max_diagonal_track() {
+        // a single 500 m cell 524 m north-east of the position near the equator
+        let spike = |lat: f64, lon: f64| {
+            if (lat - (0.5 + 4.0 * STEP)).abs() < STEP / 2.0
+                && (lon - (8.5 + 4.0 * STEP)).abs() < STEP / 2.0
+            {
+                500
+            } else {
+                100
+            }
+        };
+        let mut reader = MemoryReader::<1>::new();
+        reader.add_tile(0, 8, synthetic_tile((0, 8), spike)).ok();
+        let mut source = Tile::<MemoryReader<1>>::new(Resolution::SRTM3, reader);
+        let corridor = Corridor {
+            position: Coord::new(0.5, 8.5),
+            track: 45.0,
+            speed: 50.0,
+            lookahead: 13.0,
+            half_width: 100.0,
+        };
+        let needed = clearance::sample_count(&corridor, Resolution::SRTM3);
+        let result = clearance::corridor_max(&mut source, &corridor, needed).unwrap();
+        assert_eq!(result.height, 500);
+        assert!((result.along - 524.0).abs() < 1.0);
+        assert!(result.across.abs() < 1.0);
+        assert_eq!(result.samples, needed);
+    }
+
+    #[test]
+    fn test_flow_direction_and_accumulation() {
+        let mut source = synthetic_source(valley);
+        let mut heights = [0i16; 21 * 21];
+        let grid =
+            Grid::read(&mut source, (49.5, 8.5 - 10.0 * STEP), 21, 21, &mut heights).unwrap();
+        let mut directions = [0u8; 21 * 21];
+        flow::flow_direction(&grid, &mut directions).unwrap();
+        assert_eq!(directions[5 * 21 + 10], flow::SOUTH);
+        assert_eq!(directions[5 * 21 + 14], flow::WEST);
+        assert_eq!(directions[5 * 21 + 3], flow::EAST);
+        assert_eq!(
+            directions[20 * 21 + 10],
+            flow::SOUTH,
+            "outlet drains off the grid"
+        );
+        assert_eq!(flow::offset(flow::SOUTH_WEST), Some((1, -1)));
+        assert_eq!(flow::offset(0), None);
+
+        let mut accumulation = [0u32; 21 * 21];
+        let mut indegree = [0u8; 21 * 21];
+        flow::flow_accumulation(&grid, &directions, &mut accumulation, &mut indegree).unwrap();
+        assert_eq!(accumulation[20 * 21 + 10], 21 * 21);
+        assert_eq!(accumulation[0], 1);
+        assert_eq!(accumulation[5 * 21 + 10], 6 * 21);
+        let streams = accumulation.iter().filter(|count| **count >= 100).count();
+        assert_eq!(streams, 17, "center column below row 3");
+    }
+
+    #[test]
+    fn test_flow_flats_and_pits() {
+        // a flat drains over the border, every cell reaches an edge
+        let flat = [100i16; 7 * 7];
+        let grid = Grid::new(&flat, 7, 7, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let mut directions = [0u8; 7 * 7];
+        flow::flow_direction(&grid, &mut directions).unwrap();
+        assert!(directions.iter().all(|direction| *direction != 0));
+        assert_eq!(directions[0], flow::NORTH_WEST);
+        assert_eq!(directions[3 * 7 + 6], flow::EAST);
+        let mut accumulation = [0u32; 7 * 7];
+        let mut indegree = [0u8; 7 * 7];
+        flow::flow_accumulation(&grid, &directions, &mut accumulation, &mut indegree).unwrap();
+        let outflow: u32 = (0..7 * 7)
+            .filter(|&i| {
+                let (d_row, d_col) = flow::offset(directions[i]).unwrap();
+                let (row, col) = ((i / 7) as isize + d_row, (i % 7) as isize + d_col);
+                !(0..7).contains(&row) || !(0..7).contains(&col)
+            })
+            .map(|i| accumulation[i])
+            .sum();
+        assert_eq!(outflow, 7 * 7);
+
+        // a pit collects everything, a void takes the flow of its neighbours
+        #[rustfmt::skip]
+        let mut pit = [
+            9, 9, 9, 9, 9,
+            9, 5, 5, 5, 9,
+            9, 5, 1, 5, 9,
+            9, 5, 5, 5, 9,
+            9, 9, 9, 9, 9,
+        ];
+        let grid = Grid::new(&pit, 5, 5, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let mut directions = [0u8; 5 * 5];
+        let mut accumulation = [0u32; 5 * 5];
+        let mut indegree = [0u8; 5 * 5];
+        flow::flow_direction(&grid, &mut directions).unwrap();
+        flow::flow_accumulation(&grid, &directions, &mut accumulation, &mut indegree).unwrap();
+        assert_eq!(directions[2 * 5 + 2], 0);
+        assert_eq!(accumulation[2 * 5 + 2], 25);
+
+        pit[2 * 5 + 2] = srtm_embedded::tiles::VOID_VALUE;
+        let grid = Grid::new(&pit, 5, 5, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        flow::flow_direction(&grid, &mut directions).unwrap();
+        flow::flow_accumulation(&grid, &directions, &mut accumulation, &mut indegree).unwrap();
+        assert_eq!(directions[5 + 1], flow::SOUTH_EAST);
+        assert_eq!(directions[2 * 5 + 2], 0);
+        assert_eq!(accumulation[2 * 5 + 2], 0);
+        assert_eq!(accumulation.iter().sum::<u32>(), 24 + 16);
+        assert_eq!(
+            flow::flow_accumulation(&grid, &directions, &mut accumulation[..10], &mut indegree),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_fill_depressions() {
+        // bowl with a pit in the middle and an outlet on the west border
+        #[rustfmt::skip]
+        let mut bowl = [
+            9, 9, 9, 9, 9,
+            9, 5, 5, 5, 9,
+            3, 5, 1, 5, 9,
+            9, 5, 5, 5, 9,
+            9, 9, 9, 9, 9,
+        ];
+        let grid = Grid::new(&bowl, 5, 5, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let mut filled = [0f32; 5 * 5];
+        let mut heap = [0u32; 5 * 5];
+        fill::fill_depressions(&grid, 0.0, &mut filled, &mut heap).unwrap();
+        assert_eq!(filled[2 * 5 + 2], 5.0);
+        assert!(
+            (0..5 * 5)
+                .filter(|&i| i != 2 * 5 + 2)
+                .all(|i| filled[i] == bowl[i] as f32)
+        );
+
+        // with a gradient the filled pit drains and all water reaches the outlet
+        fill::fill_depressions(&grid, 0.5, &mut filled, &mut heap).unwrap();
+        assert_eq!(filled[2 * 5 + 2], 5.5);
+        let filled_grid = Grid::new(&filled, 5, 5, grid.origin, grid.resolution).unwrap();
+        let mut directions = [0u8; 5 * 5];
+        let mut accumulation = [0u32; 5 * 5];
+        let mut indegree = [0u8; 5 * 5];
+        flow::flow_direction(&filled_grid, &mut directions).unwrap();
+        flow::flow_accumulation(&filled_grid, &directions, &mut accumulation, &mut indegree)
+            .unwrap();
+        assert_eq!(directions[2 * 5 + 2], flow::WEST);
+        assert_eq!(accumulation[2 * 5], 25);
+
+        // voids are filled from their surroundings
+        bowl[2 * 5 + 2] = srtm_embedded::tiles::VOID_VALUE;
+        bowl[3 * 5 + 3] = srtm_embedded::tiles::VOID_VALUE;
+        let grid = Grid::new(&bowl, 5, 5, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        fill::fill_depressions(&grid, 0.0, &mut filled, &mut heap).unwrap();
+        assert_eq!((filled[2 * 5 + 2], filled[3 * 5 + 3]), (5.0, 5.0));
+        assert!(filled.iter().all(|height| !height.is_nan()));
+
+        assert_eq!(
+            fill::fill_depressions(&grid, -1.0, &mut filled, &mut heap),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+        assert_eq!(
+            fill::fill_depressions(&grid, 0.0, &mut filled, &mut heap[..24]),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_watershed() {
+        let mut source = synthetic_source(valley);
+        let mut heights = [0i16; 21 * 21];
+        let grid =
+            Grid::read(&mut source, (49.5, 8.5 - 10.0 * STEP), 21, 21, &mut heights).unwrap();
+        let mut filled = [0f32; 21 * 21];
+        let mut heap = [0u32; 21 * 21];
+        fill::fill_depressions(&grid, 0.01, &mut filled, &mut heap).unwrap();
+        let filled = Grid::new(&filled, 21, 21, grid.origin, grid.resolution).unwrap();
+        let mut directions = [0u8; 21 * 21];
+        let mut accumulation = [0u32; 21 * 21];
+        let mut indegree = [0u8; 21 * 21];
+        flow::flow_direction(&filled, &mut directions).unwrap();
+        flow::flow_accumulation(&filled, &directions, &mut accumulation, &mut indegree).unwrap();
+
+        // a pour point next to the stream moves onto it, and downstream
+        let pour_point = grid.coord(10, 11);
+        let outlet = watershed::snap_pour_point(&filled, &accumulation, pour_point, 150.0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(outlet, (11, 10));
+        assert_eq!(
+            watershed::snap_pour_point(&filled, &accumulation, pour_point, 10.0),
+            Ok(Some((10, 11)))
+        );
+        assert_eq!(
+            watershed::snap_pour_point(&filled, &accumulation, (49.0, 8.0), 150.0),
+            Ok(None)
+        );
+        assert_eq!(
+            watershed::snap_pour_point(&filled, &accumulation[..20], pour_point, 150.0),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+
+        let mut mask = [0u8; 21 * 21];
+        let result = watershed::watershed(&filled, &directions, outlet, &mut mask).unwrap();
+        assert_eq!(result.cells, 12 * 21);
+        assert_eq!(result.cells, accumulation[11 * 21 + 10] as usize);
+        assert_eq!(result.outlet, grid.coord(11, 10));
+        assert!(
+            mask[..12 * 21]
+                .iter()
+                .all(|value| *value == watershed::INSIDE)
+        );
+        assert!(mask[12 * 21..].iter().all(|value| *value == 0));
+        let (dx, dy) = grid.cell_size(6);
+        assert!((result.area / (dx * dy * 252.0) - 1.0).abs() < 1e-3);
+
+        // a side slope cell only drains itself
+        let result = watershed::watershed(&filled, &directions, (5, 20), &mut mask).unwrap();
+        assert_eq!(result.cells, 1);
+        assert_eq!(
+            watershed::watershed(&filled, &directions, (21, 0), &mut mask),
+            Err(srtm_embedded::Error::IndexOutOfBounds)
+        );
+
+        #[cfg(feature = "alloc")]
+        {
+            watershed::watershed(&filled, &directions, (11, 10), &mut mask).unwrap();
+            let lines = watershed::outline(&filled, &mask).unwrap();
+            assert_eq!(lines.len(), 1);
+            assert!(!lines[0].closed);
+            let lat = grid.coord(11, 0).lat - 0.5 * STEP;
+            assert!(
+                lines[0]
+                    .points
+                    .iter()
+                    .all(|point| (point.lat - lat).abs() < 1e-9)
+            );
+        }
+    }
+
+    #[test]
+    fn test_peaks_across_tiles() {
+        // two cones on both sides of the border between N49E008 and N49E009,
+        // 1000 m and 900 m high and 24 samples apart, with a col at 830 m
+        let cones = |lat: f64, lon: f64| {
+            let cone = |top: f64, center: f64| {
+                let distance = ((lat - 49.5).powi(2) + (lon - center).powi(2)).sqrt() / STEP;
+                top - 10.0 * distance
+            };
+            cone(1000.0, 9.0 - 12.0 * STEP)
+                .max(cone(900.0, 9.0 + 12.0 * STEP))
+                .max(100.0)
+                .round() as i16
+        };
+        let mut reader = MemoryReader::<2>::new();
+        for lon in [8, 9] {
+            reader
+                .add_tile(49, lon, synthetic_tile((49, lon), cones))
+                .unwrap();
+        }
+        let mut mosaic = Mosaic::<MemoryReader<2>, 2>::new(Resolution::SRTM3);
+        for _ in 0..2 {
+            assert!(mosaic.add_reader(reader.clone()).is_ok());
+        }
+        let mut heights = [0i16; 41 * 61];
+        let origin = (49.5 + 20.0 * STEP, 9.0 - 30.0 * STEP);
+        let grid = Grid::read(&mut mosaic, origin, 41, 61, &mut heights).unwrap();
+        let mut tile = Tile::<MemoryReader<2>>::new(Resolution::SRTM3, reader);
+        for row in 0..41 {
+            for col in 0..61 {
+                let height = tile.get_height::<MemoryReader<2>>(grid.coord(row, col));
+                assert_eq!(height.map(f64::from).ok(), grid.get(row, col));
+            }
+        }
+        let mut keys = [0f32; 41 * 61];
+        let mut heap = [0u32; 41 * 61];
+        let mut visited = [0u8; (41 * 61usize).div_ceil(8)];
+        let mut found = [Peak {
+            coord: Coord::new(0.0, 0.0),
+            row: 0,
+            col: 0,
+            height: 0.0,
+            key_col: None,
+            isolation: None,
+        }; 4];
+        let count =
+            peaks::find_peaks(&grid, 50.0, &mut found, &mut keys, &mut heap, &mut visited).unwrap();
+        assert_eq!(count, 2);
+        let (high, low) = (found[0], found[1]);
+        assert_eq!((high.row, high.col, high.height), (20, 18, 1000.0));
+        assert_eq!((low.row, low.col, low.height), (20, 42, 900.0));
+        assert_eq!(high.key_col, None, "the col of the highest peak is outside");
+        assert_eq!(high.isolation, None);
+
+        let key_col = low.key_col.unwrap();
+        assert_eq!(key_col.height, 830.0);
+        assert_eq!(low.prominence(), Some(70.0));
+        let (row, col) = grid.index_of(key_col.coord).unwrap();
+        assert!(row.abs_diff(20) <= 1 && col == 35, "col at {row}, {col}");
+        assert!(key_col.coord.lon > 9.0, "the col lies in the eastern tile");
+        let (dx, _) = grid.cell_size(20);
+        let isolation = low.isolation.unwrap();
+        assert!(
+            isolation > 14.0 * dx && isolation < 15.5 * dx,
+            "isolation {isolation}"
+        );
+
+        // filtered by prominence and by the size of the output
+        let count = peaks::find_peaks(&grid, 100.0, &mut found, &mut keys, &mut heap, &mut visited)
+            .unwrap();
+        assert_eq!((count, found[0].height), (1, 1000.0));
+        let count = peaks::find_peaks(
+            &grid,
+            0.0,
+            &mut found[..1],
+            &mut keys,
+            &mut heap,
+            &mut visited,
+        )
+        .unwrap();
+        assert_eq!((count, found[0].height), (1, 1000.0));
+        assert_eq!(
+            peaks::key_col(&grid, 20, 42, &mut keys[..10], &mut heap),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_local_maximum_plateau() {
+        #[rustfmt::skip]
+        let heights = [
+            1, 1, 1, 1, 1,
+            1, 5, 5, 1, 1,
+            1, 5, 5, 1, 1,
+            1, 1, 1, 1, 1,
+        ];
+        let grid = Grid::new(&heights, 4, 5, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let maxima: Vec<(usize, usize)> = (0..4)
+            .flat_map(|row| (0..5).map(move |col| (row, col)))
+            .filter(|&(row, col)| peaks::is_local_maximum(&grid, row, col))
+            .collect();
+        assert_eq!(maxima, [(1, 1)]);
+    }
+
+    #[test]
+    fn test_find_peaks_plateaus() {
+        // a U-shaped plateau is one summit, a shelf next to a higher cell none
+        #[rustfmt::skip]
+        let heights = [
+            1, 1, 1, 1, 1, 1, 1, 1, 1,
+            1, 5, 1, 5, 1, 1, 1, 1, 1,
+            1, 5, 1, 5, 1, 3, 3, 3, 1,
+            1, 5, 5, 5, 1, 1, 1, 4, 1,
+            1, 1, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1, 1, 1, 1,
+        ];
+        let grid = Grid::new(&heights, 6, 9, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let maxima = (0..6)
+            .flat_map(|row| (0..9).map(move |col| (row, col)))
+            .filter(|&(row, col)| peaks::is_local_maximum(&grid, row, col))
+            .count();
+        assert_eq!(
+            maxima, 4,
+            "both arms, the shelf and the higher cell next to it"
+        );
+        let (mut keys, mut heap, mut visited) = ([0f32; 54], [0u32; 54], [0u8; 7]);
+        let mut found = [Peak {
+            coord: Coord::new(0.0, 0.0),
+            row: 0,
+            col: 0,
+            height: 0.0,
+            key_col: None,
+            isolation: None,
+        }; 4];
+        let count =
+            peaks::find_peaks(&grid, 0.0, &mut found, &mut keys, &mut heap, &mut visited).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!((found[0].row, found[0].col, found[0].height), (1, 1, 5.0));
+        assert_eq!((found[1].row, found[1].col, found[1].height), (3, 7, 4.0));
+        assert_eq!(
+            peaks::find_peaks(
+                &grid,
+                0.0,
+                &mut found,
+                &mut keys,
+                &mut heap,
+                &mut visited[..6]
+            ),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_contours() {
+        use srtm_embedded::contours;
+
+        // cone with its 100 m top at row 10, column 10, falling 10 m per cell
+        let mut heights = vec![0i16; 21 * 21];
+        for row in 0..21 {
+            for col in 0..21 {
+                let r = ((row as f64 - 10.0).powi(2) + (col as f64 - 10.0).powi(2)).sqrt();
+                heights[row * 21 + col] = (100.0 - 10.0 * r).round() as i16;
+            }
+        }
+        let grid = Grid::new(&heights, 21, 21, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let lines = contours::contour_level(&grid, 55.0);
+        assert_eq!(lines.len(), 1);
+        let ring = &lines[0];
+        assert!(ring.closed);
+        assert_eq!(ring.points.first(), ring.points.last());
+        let center = grid.coord(10, 10);
+        for point in &ring.points {
+            let cells = ((point.lat - center.lat).powi(2) + (point.lon - center.lon).powi(2))
+                .sqrt()
+                * 1200.0;
+            assert!((cells - 4.5).abs() < 0.3, "ring radius {cells}");
+        }
+
+        let lines = contours::contours(&grid, 20.0, 0.0).unwrap();
+        let levels: Vec<f64> = lines.iter().map(|line| line.level).collect();
+        assert_eq!(levels[0], -40.0, "the corners are at -41 m");
+        assert_eq!(*levels.last().unwrap(), 100.0);
+
+        // a void in the ring opens it
+        heights[10 * 21 + 15] = srtm_embedded::tiles::VOID_VALUE;
+        let grid = Grid::new(&heights, 21, 21, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let lines = contours::contour_level(&grid, 55.0);
+        assert_eq!(lines.len(), 1);
+        assert!(!lines[0].closed);
+
+        // a ramp gives one straight open line per level
+        let ramp: Vec<i16> = (0..10 * 10).map(|i| (i % 10) as i16 * 10).collect();
+        let grid = Grid::new(&ramp, 10, 10, (49.5, 8.5), Resolution::SRTM3).unwrap();
+        let lines = contours::contour_level(&grid, 45.0);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].points.len(), 10);
+        assert!(!lines[0].closed);
+        assert!(
+            lines[0]
+                .points
+                .iter()
+                .all(|point| (point.lon - grid.coord(0, 4).lon - 0.5 / 1200.0).abs() < 1e-9)
+        );
+        assert_eq!(
+            contours::contours(&grid, 0.0, 0.0),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+    }
+}
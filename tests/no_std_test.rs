@@ -2,9 +2,9 @@
 #[cfg(test)]
 mod tests {
 
-    use srtm_embedded::{HgtReader, Resolution, Tile, coords};
+    use srtm_embedded::{HgtReader, MemoryReader, Mosaic, Resolution, Tile, coords};
     extern crate alloc;
-    use alloc::{format, string::String};
+    use alloc::string::String;
 
     const SYS_OPENAT: usize = 257;
     const SYS_READ: usize = 0;
@@ -104,7 +104,7 @@ mod tests {
             if self.file == -1 {
                 return Err(srtm_embedded::Error::FileNotFound);
             }
-            self.file_name = format!("{}", file_name);
+            self.file_name = String::from(file_name);
             self.is_open = true;
             Ok(())
         }
@@ -118,7 +118,7 @@ mod tests {
             pos: u64,
             buff: &mut [u8; 2],
         ) -> Result<(), srtm_embedded::Error> {
-            if self.is_open == false {
+            if !self.is_open {
                 return Err(srtm_embedded::Error::NotFound);
             }
             let res = unsafe { pread(self.file, buff.as_mut_ptr(), 2, pos.try_into().unwrap()) };
@@ -131,7 +131,7 @@ mod tests {
         fn close_hgt_file(&mut self) -> Result<(), srtm_embedded::Error> {
             if self.is_open {
                 unsafe {
-                    close(self.file.try_into().unwrap());
+                    close(self.file);
                 }
                 self.file = -1;
                 self.is_open = false;
@@ -156,4 +156,156 @@ mod tests {
         assert_eq!(height, Ok(126), "Height retrieval failed");
         assert_ne!(height, Ok(128), "Height retrieval failed");
     }
+
+    #[test]
+    fn test_read_window_default_block_read() {
+        let reader = HgtReaderNoStd::new();
+        let mut tile = Tile::<HgtReaderNoStd>::new(Resolution::SRTM3, reader);
+        let mut window = [0i16; 9];
+        assert_eq!(tile.read_window((49.1, 8.2), 3, 3, &mut window), Ok(()));
+        assert_eq!(window[0], 126, "Window retrieval failed");
+        let height = tile.get_height::<HgtReaderNoStd>((49.1 - 2.0 / 1200.0, 8.2 + 2.0 / 1200.0));
+        assert_eq!(height, Ok(window[8]), "Window retrieval failed");
+        // half a sample is a caller error, not a void sample
+        let mut reader = HgtReaderNoStd::new();
+        assert_eq!(
+            reader.read_hgt_block(0, &mut [0u8; 3]),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+    }
+
+    static N49E008: &[u8] = include_bytes!("../N49E008.hgt");
+    static N49E009: &[u8] = include_bytes!("../N49E009.hgt");
+
+    #[test]
+    fn test_memory_reader() {
+        let mut reader = MemoryReader::<2>::new();
+        assert_eq!(reader.add_tile(49, 8, N49E008), Ok(()));
+        assert_eq!(reader.add_tile(49, 9, N49E009), Ok(()));
+        assert_eq!(
+            reader.add_tile(50, 9, N49E009),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+        assert_eq!(
+            reader.add_tile(90, 8, N49E008),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+        assert_eq!(
+            reader.add_tile(49, 180, N49E008),
+            Err(srtm_embedded::Error::InvalidParameter)
+        );
+        let mut tile = Tile::<MemoryReader<2>>::new(Resolution::SRTM3, reader);
+        assert_eq!(tile.get_height::<MemoryReader<2>>((49.1, 8.2)), Ok(126));
+        assert_eq!(tile.get_height::<MemoryReader<2>>((49.1, 9.2)), Ok(205));
+
+        let mut window = [0i16; 9];
+        let mut file_window = [0i16; 9];
+        tile.read_window((49.1, 8.2), 3, 3, &mut window).unwrap();
+        let mut file_tile = Tile::<HgtReaderNoStd>::new(Resolution::SRTM3, HgtReaderNoStd::new());
+        file_tile
+            .read_window((49.1, 8.2), 3, 3, &mut file_window)
+            .unwrap();
+        assert_eq!(window, file_window);
+
+        assert_eq!(
+            tile.get_height::<MemoryReader<2>>((48.1, 8.2)),
+            Err(srtm_embedded::Error::FileNotFound)
+        );
+        let mut reader = MemoryReader::<1>::new();
+        reader.add_tile(49, 8, N49E008).ok();
+        let mut tile = Tile::<MemoryReader<1>>::new(Resolution::SRTM1, reader);
+        assert_eq!(
+            tile.get_height::<MemoryReader<1>>((49.1, 8.2)),
+            Err(srtm_embedded::Error::Filesize)
+        );
+
+        // blocks past the end of the data, also if the end overflows
+        let mut reader = MemoryReader::<1>::new();
+        reader.add_tile(49, 8, N49E008).ok();
+        reader.open_hgt_file("N49E008.hgt").unwrap();
+        let end = N49E008.len() as u64;
+        assert_eq!(reader.read_hgt_block(end - 2, &mut [0u8; 2]), Ok(()));
+        assert_eq!(
+            reader.read_hgt_block(end, &mut [0u8; 2]),
+            Err(srtm_embedded::Error::IndexOutOfBounds)
+        );
+        assert_eq!(
+            reader.read_hgt_block(u64::MAX, &mut [0u8; 2]),
+            Err(srtm_embedded::Error::IndexOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_memory_reader_mosaic() {
+        let mut reader = MemoryReader::<2>::new();
+        reader.add_tile(49, 8, N49E008).unwrap();
+        reader.add_tile(49, 9, N49E009).unwrap();
+        let mut mosaic = Mosaic::<MemoryReader<2>, 2>::new(Resolution::SRTM3);
+        for _ in 0..2 {
+            assert!(mosaic.add_reader(reader.clone()).is_ok());
+        }
+        let mut tile = Tile::<MemoryReader<2>>::new(Resolution::SRTM3, reader);
+        assert_eq!(mosaic.get_height((49.1, 8.2)), Ok(126));
+        assert_eq!(mosaic.get_height((49.1, 9.2)), Ok(205));
+
+        // a window across the edge of both tiles
+        let mut window = [0i16; 6];
+        assert_eq!(mosaic.read_window((49.1, 8.999), 2, 3, &mut window), Ok(()));
+        let (top, left) = ((49.1f64 * 1200.0).round(), (8.999f64 * 1200.0).round());
+        for row in 0..2 {
+            for col in 0..3 {
+                let lat = (top - row as f64) / 1200.0;
+                let lon = (left + col as f64) / 1200.0;
+                assert_eq!(
+                    tile.get_height::<MemoryReader<2>>((lat, lon)),
+                    Ok(window[row * 3 + col])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_mosaic_readers_with_separate_tiles() {
+        let mut mosaic = Mosaic::<MemoryReader<1>, 2>::new(Resolution::SRTM3);
+        for (lon, data) in [(8, N49E008), (9, N49E009)] {
+            let mut reader = MemoryReader::<1>::new();
+            reader.add_tile(49, lon, data).ok();
+            assert!(mosaic.add_reader(reader).is_ok());
+        }
+        // the first reader does not hold N49E009, the second one is used
+        assert_eq!(mosaic.get_height((49.1, 9.2)), Ok(205));
+        assert_eq!(mosaic.get_height((49.1, 8.2)), Ok(126));
+        // a tile no reader holds keeps the open tiles
+        assert_eq!(
+            mosaic.get_height((50.1, 8.2)),
+            Err(srtm_embedded::Error::FileNotFound)
+        );
+        assert_eq!(mosaic.get_height((49.1, 9.2)), Ok(205));
+        assert_eq!(mosaic.get_height((49.1, 8.2)), Ok(126));
+    }
+
+    #[test]
+    fn test_mosaic_window_on_edge_without_neighbour() {
+        let mut reader = MemoryReader::<1>::new();
+        reader.add_tile(49, 8, N49E008).unwrap();
+        let mut mosaic = Mosaic::<MemoryReader<1>, 2>::new(Resolution::SRTM3);
+        assert!(mosaic.add_reader(reader).is_ok());
+        // the eastern and southern edges of N49E008 without N49E009 or N48E008
+        let mut window = [0i16; 2];
+        assert_eq!(mosaic.read_window((49.5, 9.0), 2, 1, &mut window), Ok(()));
+        assert_eq!(mosaic.get_height((49.5, 9.0)), Ok(window[0]));
+        assert_eq!(mosaic.get_height((49.5 - 1.0 / 1200.0, 9.0)), Ok(window[1]));
+        assert_eq!(window[0], 432);
+        assert_eq!(mosaic.read_window((49.0, 8.5), 1, 2, &mut window), Ok(()));
+        assert_eq!(window[0], 175);
+        assert_eq!(mosaic.get_height((49.0, 8.5 + 1.0 / 1200.0)), Ok(window[1]));
+        // the corner shared by four tiles
+        assert_eq!(mosaic.read_window((49.0, 9.0), 1, 1, &mut window), Ok(()));
+        assert_eq!(mosaic.get_height((49.0, 9.0)), Ok(window[0]));
+        // samples outside the tile still need the neighbour
+        assert_eq!(
+            mosaic.read_window((49.5, 9.0), 1, 2, &mut window),
+            Err(srtm_embedded::Error::FileNotFound)
+        );
+    }
 }
@@ -1,7 +1,9 @@
 #[cfg(test)]
 mod tests {
 
-    use srtm_embedded::{HgtReader, Resolution, Tile, coords};
+    use srtm_embedded::{
+        CachedReader, ElevationSource, HgtReader, Mosaic, Resolution, Tile, coords,
+    };
     use std::fs::File;
     use std::io::{Read, Seek, SeekFrom};
     use std::result::Result;
@@ -88,6 +90,23 @@ mod tests {
                 Err(srtm_embedded::Error::NotFound)
             }
         }
+        /// Reads a run of samples with a single seek.
+        fn read_hgt_block(
+            &mut self,
+            pos: u64,
+            buff: &mut [u8],
+        ) -> std::result::Result<(), srtm_embedded::Error> {
+            if let Some(ref mut file) = self.file {
+                file.seek(SeekFrom::Start(pos))
+                    .map_err(|_| srtm_embedded::Error::Filesize)?;
+
+                file.read_exact(buff)
+                    .map_err(|_| srtm_embedded::Error::Filesize)?;
+                Ok(())
+            } else {
+                Err(srtm_embedded::Error::NotFound)
+            }
+        }
         fn close_hgt_file(&mut self) -> Result<(), srtm_embedded::Error> {
             if self.is_open {
                 self.file = None;
@@ -159,4 +178,227 @@ mod tests {
             "Height retrieval failed"
         );
     }
+
+    #[test]
+    fn test_read_window() {
+        let reader = HgtReaderStd::new();
+        let mut tile = Tile::<HgtReaderStd>::new(Resolution::SRTM3, reader);
+        let mut window = [0i16; 3 * 200];
+        tile.read_window((49.1, 8.2), 3, 200, &mut window).unwrap();
+        assert_eq!(window[0], 126, "Window origin mismatch");
+        let step = 1.0 / 1200.0;
+        for (row, col) in [(0, 1), (1, 0), (2, 150), (2, 199)] {
+            let height = tile
+                .get_height::<HgtReaderStd>((49.1 - row as f64 * step, 8.2 + col as f64 * step));
+            assert_eq!(
+                height,
+                Ok(window[row * 200 + col]),
+                "Window sample mismatch"
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_window_errors() {
+        let reader = HgtReaderStd::new();
+        let mut tile = Tile::<HgtReaderStd>::new(Resolution::SRTM3, reader);
+        let mut window = [0i16; 4];
+        assert_eq!(
+            tile.read_window((49.1, 8.2), 3, 3, &mut window),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+        assert_eq!(
+            tile.read_window((49.1, 8.9999), 2, 2, &mut window),
+            Err(srtm_embedded::Error::IndexOutOfBounds)
+        );
+        // rows * cols overflows and must not pass the size check
+        assert_eq!(
+            tile.read_window((49.1, 8.2), usize::MAX / 2 + 1, 2, &mut window),
+            Err(srtm_embedded::Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_tile_tracks_origin() {
+        let reader = HgtReaderStd::new();
+        let mut tile = Tile::<HgtReaderStd>::new(Resolution::SRTM3, reader);
+        assert_eq!(tile.origin(), None);
+        assert!(!tile.contains((49.1, 8.2)));
+        assert_eq!(
+            tile.coord_to_sample((49.1, 8.2)),
+            Err(srtm_embedded::Error::NotFound)
+        );
+
+        tile.get_height::<HgtReaderStd>((49.1, 8.2)).unwrap();
+        assert_eq!(tile.origin(), Some((49, 8)));
+        assert_eq!((tile.latitude, tile.longitude), (49, 8));
+        assert_eq!(
+            tile.bounds(),
+            Some(srtm_embedded::Bounds::new((49.0, 8.0), (50.0, 9.0)))
+        );
+        assert!(tile.contains((49.5, 9.0)));
+        assert!(!tile.contains((49.5, 9.2)));
+
+        tile.get_height::<HgtReaderStd>((49.1, 9.2)).unwrap();
+        assert_eq!(tile.origin(), Some((49, 9)));
+    }
+
+    #[test]
+    fn test_tile_sample_conversion() {
+        let reader = HgtReaderStd::new();
+        let mut tile = Tile::<HgtReaderStd>::new(Resolution::SRTM3, reader);
+        tile.get_height::<HgtReaderStd>((49.1, 8.2)).unwrap();
+        assert_eq!(tile.coord_to_sample((49.1, 8.2)), Ok((1080, 240)));
+        assert_eq!(tile.coord_to_sample((50.0, 8.0)), Ok((0, 0)));
+        assert_eq!(tile.coord_to_sample((49.0, 9.0)), Ok((1200, 1200)));
+        assert_eq!(
+            tile.coord_to_sample((48.9, 8.2)),
+            Err(srtm_embedded::Error::IndexOutOfBounds)
+        );
+
+        let coord = tile.sample_to_coord(1080, 240).unwrap();
+        assert!((coord.lat - 49.1).abs() < 1e-9 && (coord.lon - 8.2).abs() < 1e-9);
+        assert_eq!(
+            tile.sample_to_coord(1201, 0),
+            Err(srtm_embedded::Error::IndexOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_mosaic_window_across_tiles() {
+        let mut mosaic = Mosaic::<HgtReaderStd, 2>::new(Resolution::SRTM3);
+        assert!(mosaic.add_reader(HgtReaderStd::new()).is_ok());
+        assert!(mosaic.add_reader(HgtReaderStd::new()).is_ok());
+        assert!(mosaic.add_reader(HgtReaderStd::new()).is_err());
+
+        let step = 1.0 / 1200.0;
+        let (lat, lon) = (49.5, 9.0 - 5.0 * step);
+        let mut window = [0i16; 4 * 12];
+        mosaic.read_window((lat, lon), 4, 12, &mut window).unwrap();
+
+        let mut tile = Tile::<HgtReaderStd>::new(Resolution::SRTM3, HgtReaderStd::new());
+        for row in 0..4 {
+            for col in 0..12 {
+                let coord = (lat - row as f64 * step, lon + col as f64 * step);
+                assert_eq!(
+                    tile.get_height::<HgtReaderStd>(coord),
+                    Ok(window[row * 12 + col]),
+                    "Mosaic sample mismatch at {row}/{col}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_mosaic_shared_edge() {
+        let mut mosaic = Mosaic::<HgtReaderStd, 2>::new(Resolution::SRTM3);
+        mosaic.add_reader(HgtReaderStd::new()).ok();
+        mosaic.add_reader(HgtReaderStd::new()).ok();
+        let mut tile = Tile::<HgtReaderStd>::new(Resolution::SRTM3, HgtReaderStd::new());
+
+        // the eastern edge of N49E008 is the western edge of N49E009
+        let mut west = [0i16; 2];
+        tile.read_window((49.5, 9.0 - 1.0 / 1200.0), 1, 2, &mut west)
+            .unwrap();
+        assert_eq!(tile.get_height::<HgtReaderStd>((49.5, 9.0)), Ok(west[1]));
+        assert_eq!(mosaic.get_height((49.5, 9.0)), Ok(west[1]));
+
+        // N50E008 is not available, its southern edge is served by N49E008
+        let mut edge = [0i16; 2];
+        tile.read_window((50.0, 8.5), 2, 1, &mut edge).unwrap();
+        assert_eq!(tile.origin(), Some((49, 8)));
+        let height = mosaic.get_height((50.0, 8.5)).unwrap();
+        assert_eq!(tile.get_height::<HgtReaderStd>((50.0, 8.5)), Ok(height));
+        let mut window = [0i16; 2];
+        mosaic.read_window((50.0, 8.5), 2, 1, &mut window).unwrap();
+        assert_eq!(window, edge);
+
+        // a fresh tile falls back to N49E008 as well
+        let mut fresh = Tile::<HgtReaderStd>::new(Resolution::SRTM3, HgtReaderStd::new());
+        assert_eq!(fresh.get_height::<HgtReaderStd>((50.0, 8.5)), Ok(height));
+        assert_eq!(fresh.origin(), Some((49, 8)));
+    }
+
+    #[test]
+    fn test_interpolated_height_on_tile_edges() {
+        let mut tile = Tile::<HgtReaderStd>::new(Resolution::SRTM3, HgtReaderStd::new());
+        // southern edge row at an exact integer latitude
+        let height = tile.get_height::<HgtReaderStd>((49.0, 8.5)).unwrap();
+        let interpolated = tile.interpolated_height((49.0, 8.5).into()).unwrap();
+        assert!((interpolated - height as f64).abs() < 1e-6);
+        // just west of the eastern edge column, and on it
+        let mut edge = [0i16; 2];
+        tile.read_window((49.5, 9.0 - 1.0 / 1200.0), 1, 2, &mut edge)
+            .unwrap();
+        let interpolated = tile.interpolated_height((49.5, 8.999999).into()).unwrap();
+        assert!(interpolated >= edge[0].min(edge[1]) as f64);
+        assert!(interpolated <= edge[0].max(edge[1]) as f64);
+        let interpolated = tile.interpolated_height((49.5, 9.0).into()).unwrap();
+        assert!((interpolated - edge[1] as f64).abs() < 1e-6);
+
+        // line of sight along the southern edge
+        let options = srtm_embedded::los::LosOptions::default();
+        let from = srtm_embedded::los::Position::above_ground((49.0, 8.4), 1000.0);
+        let to = srtm_embedded::los::Position::above_ground((49.0, 8.6), 1000.0);
+        assert!(srtm_embedded::los::line_of_sight(&mut tile, from, to, &options).is_ok());
+    }
+
+    #[test]
+    fn test_interpolated_height() {
+        let mut mosaic = Mosaic::<HgtReaderStd, 2>::new(Resolution::SRTM3);
+        mosaic.add_reader(HgtReaderStd::new()).ok();
+        let step = 1.0 / 1200.0;
+        let mut window = [0i16; 4];
+        mosaic
+            .read_window((49.1 + step, 8.2), 2, 2, &mut window)
+            .unwrap();
+        let corner = mosaic.interpolated_height((49.1, 8.2).into()).unwrap();
+        assert!((corner - window[2] as f64).abs() < 1e-6);
+        let center = mosaic
+            .interpolated_height((49.1 + step / 2.0, 8.2 + step / 2.0).into())
+            .unwrap();
+        let mean = window.iter().map(|h| *h as f64).sum::<f64>() / 4.0;
+        assert!((center - mean).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cached_reader() {
+        let reader = CachedReader::<_, 3, 2402>::new(HgtReaderStd::new());
+        let mut tile = Tile::<CachedReader<HgtReaderStd, 3, 2402>>::new(Resolution::SRTM3, reader);
+        let mut plain = Tile::<HgtReaderStd>::new(Resolution::SRTM3, HgtReaderStd::new());
+
+        let mut cached_window = [0i16; 3 * 1201];
+        let mut window = [0i16; 3 * 1201];
+        tile.read_window((49.1, 8.0), 3, 1201, &mut cached_window)
+            .unwrap();
+        plain
+            .read_window((49.1, 8.0), 3, 1201, &mut window)
+            .unwrap();
+        assert_eq!(cached_window, window);
+
+        let step = 1.0 / 1200.0;
+        for i in 0..3 {
+            let coord = (49.1 - i as f64 * step, 8.2);
+            assert_eq!(
+                tile.get_height::<CachedReader<HgtReaderStd, 3, 2402>>(coord),
+                plain.get_height::<HgtReaderStd>(coord)
+            );
+        }
+        let reader = tile.reader();
+        assert_eq!(reader.misses(), 3, "one miss per row");
+        // 10 block reads per window row, 3 single samples
+        assert_eq!(reader.hits(), 27 + 3);
+
+        // reading from the last row of the file
+        assert_eq!(
+            tile.get_height::<CachedReader<HgtReaderStd, 3, 2402>>((49.0, 9.0)),
+            plain.get_height::<HgtReaderStd>((49.0, 9.0))
+        );
+        // switching the file drops the cache
+        tile.get_height::<CachedReader<HgtReaderStd, 3, 2402>>((49.1, 9.2))
+            .unwrap();
+        tile.get_height::<CachedReader<HgtReaderStd, 3, 2402>>((49.1, 8.2))
+            .unwrap();
+        assert_eq!(tile.reader().misses(), 6);
+    }
 }